@@ -9,6 +9,8 @@ uniform vec2 RES;
 uniform vec2 CAM;
 uniform vec2 DIRECTION = vec2(1.0, 0.0);
 uniform float FRAME;
+//tile size relative to the default one, keeps the clouds the same size in the world when zooming
+uniform float ZOOM = 1.0;
 
 const float FACTOR = 150;
 const float SPEED = 1;
@@ -34,8 +36,8 @@ float noise( in vec2 p ) {
 }
 
 void main() {
-    vec2 pos = (FRAME * SPEED * DIRECTION) - CAM;
-    vec2 uv = (fUv - pos / RES) * RES / FACTOR;
+    vec2 world = (fUv * RES + CAM) / ZOOM - FRAME * SPEED * DIRECTION;
+    vec2 uv = world / FACTOR;
     float noise = (noise(uv) / 7.0 + noise(uv / 1.5) / 3.0 + noise(uv / 5.0) * 1.2) * EFFECT + (1.0 - EFFECT);
     //float noise = noise((fUv - CAM / RES) * FACTOR) * EFFECT + (1.0 - EFFECT);
     vec4 color = texture(COLOR, fUv);
@@ -18,15 +18,18 @@ use std::ops::Deref;
 pub enum Fill {
     Color(RgbColor),
     Drawable(Drawable, Orientation),
+    /// Drawable blended with a color, the alpha of the color decides how strong the tint is.
+    TintedDrawable(Drawable, Orientation, RgbColor),
 }
 
 pub fn tile_rect(
-    ctx: &mut impl RenderContext,
+    ctx: &mut (impl RenderContext + ?Sized),
     uv: [(f32, f32); 4],
     tile_rect: &SimpleRect,
     view_area: &SimpleRect,
     tex: &Texture,
     y: i32,
+    tint: Option<RgbColor>,
 ) {
     let controller = ctx.controller();
 
@@ -36,7 +39,7 @@ pub fn tile_rect(
     let y2 = tile_rect.y as f32 + tile_rect.height as f32 - view_area.y as f32;
 
     //replace with biome tint in future
-    let tint = None.unwrap_or(RgbColor::transparent());
+    let tint = tint.unwrap_or(RgbColor::transparent());
 
     controller.push_quad(Quad {
         points: [
@@ -76,6 +79,23 @@ pub fn tile_rect(
     });
 }
 
+/// Draws a drawable into a world space rect. Works with `dyn` contexts, so it can be used by custom tile drawers.
+pub fn drawable_rect(
+    ctx: &mut (impl RenderContext + ?Sized),
+    view_area: &SimpleRect,
+    rect: &SimpleRect,
+    drawable: &Drawable,
+    orientation: Orientation,
+    y: i32,
+) {
+    if view_area.intersects(rect) {
+        let (tex, uv) = drawable.get_texture_or_default(R.deref().deref());
+        let uv = Texture::get_uv_inner_static(uv);
+        let uv = orientation.apply(uv);
+        tile_rect(ctx, uv, rect, view_area, tex, y, None);
+    }
+}
+
 pub fn rect(ctx: &mut impl WideRenderContext, x: i32, y: i32, w: i32, h: i32, fill: Fill, z: f32) {
     match fill {
         Fill::Color(col) => {
@@ -86,24 +106,44 @@ pub fn rect(ctx: &mut impl WideRenderContext, x: i32, y: i32, w: i32, h: i32, fi
             });
         }
         Fill::Drawable(drawable, orientation) => {
-            let (tex, uv) = drawable.get_texture_or_default(R.deref().deref());
-            let uv = Texture::get_uv_inner_static(uv);
-            let uv = orientation.apply(uv);
+            textured_rect(ctx, x, y, w, h, &drawable, orientation, None, z);
+        }
+        Fill::TintedDrawable(drawable, orientation, tint) => {
+            textured_rect(ctx, x, y, w, h, &drawable, orientation, Some(tint), z);
+        }
+    };
+}
 
-            let mut v1 = shapes::vertex1(x, y, tex.id, uv[0]);
-            let mut v2 = shapes::vertex1(x, y + h, tex.id, uv[1]);
-            let mut v3 = shapes::vertex1(x + w, y + h, tex.id, uv[2]);
-            let mut v4 = shapes::vertex1(x + w, y, tex.id, uv[3]);
+fn textured_rect(
+    ctx: &mut impl WideRenderContext,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    drawable: &Drawable,
+    orientation: Orientation,
+    tint: Option<RgbColor>,
+    z: f32,
+) {
+    let (tex, uv) = drawable.get_texture_or_default(R.deref().deref());
+    let uv = Texture::get_uv_inner_static(uv);
+    let uv = orientation.apply(uv);
 
-            for v in [&mut v1, &mut v2, &mut v3, &mut v4] {
-                v.pos.2 = z;
-            }
+    let mut v1 = shapes::vertex1(x, y, tex.id, uv[0]);
+    let mut v2 = shapes::vertex1(x, y + h, tex.id, uv[1]);
+    let mut v3 = shapes::vertex1(x + w, y + h, tex.id, uv[2]);
+    let mut v4 = shapes::vertex1(x + w, y, tex.id, uv[3]);
 
-            ctx.controller().push_quad(Quad {
-                points: [v1, v2, v3, v4],
-            });
+    for v in [&mut v1, &mut v2, &mut v3, &mut v4] {
+        v.pos.2 = z;
+        if let Some(tint) = &tint {
+            v.color = tint.as_vec4();
         }
-    };
+    }
+
+    ctx.controller().push_quad(Quad {
+        points: [v1, v2, v3, v4],
+    });
 }
 
 pub fn draw_in_world(
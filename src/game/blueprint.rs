@@ -0,0 +1,493 @@
+use crate::game::sanitize_file_name;
+use crate::world::{tile_pos_at, ClientWorld};
+use api::world::tiles::{Orientation, TileKind};
+use api::world::TileExtent;
+use bytebuffer::ByteBuffer;
+use log::{debug, error, warn};
+use mvengine::game::fs::smartdir::SmartDir;
+use mvutils::save::Savable;
+use mvutils::Savable;
+use std::fs;
+use std::path::PathBuf;
+
+pub const BLUEPRINT_DIR: &str = "blueprints";
+pub const BLUEPRINT_VERSION: u32 = 1;
+/// Prefix of exported blueprints, followed by the version and the hex encoded data.
+const TEXT_PREFIX: &str = "FIBP";
+const FILE_EXT: &str = "bp";
+const TEXT_EXT: &str = "txt";
+/// Largest area that can be copied at once.
+pub const MAX_BLUEPRINT_SIZE: i32 = 64;
+
+#[derive(Clone, Savable)]
+pub struct BlueprintTile {
+    pub x: i32,
+    pub y: i32,
+    pub id: TileKind,
+    orientation: u8,
+}
+
+impl BlueprintTile {
+    pub fn orientation(&self) -> Orientation {
+        orientation_from_u8(self.orientation)
+    }
+}
+
+#[derive(Clone, Savable)]
+pub struct BlueprintMultiTile {
+    pub x: i32,
+    pub y: i32,
+    pub mt_id: u32,
+    pub extent: TileExtent,
+}
+
+/// Tiles and multitiles of an area, relative to its bottom left corner.
+#[derive(Clone, Savable)]
+pub struct Blueprint {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    pub tiles: Vec<BlueprintTile>,
+    pub multitiles: Vec<BlueprintMultiTile>,
+}
+
+impl Blueprint {
+    /// Copies everything between the two corners. Multitiles are only included if their origin is inside.
+    pub fn capture(world: &ClientWorld, a: (i32, i32), b: (i32, i32), name: String) -> Self {
+        let x1 = a.0.min(b.0);
+        let y1 = a.1.min(b.1);
+        let x2 = a.0.max(b.0).min(x1 + MAX_BLUEPRINT_SIZE - 1);
+        let y2 = a.1.max(b.1).min(y1 + MAX_BLUEPRINT_SIZE - 1);
+
+        let mut tiles = vec![];
+        let mut multitiles = vec![];
+        for x in x1..=x2 {
+            for y in y1..=y2 {
+                if let Some(tile) = world.tile_at((x, y)) {
+                    if tile.id != 0 {
+                        tiles.push(BlueprintTile {
+                            x: x - x1,
+                            y: y - y1,
+                            id: tile.id,
+                            orientation: orientation_to_u8(tile.orientation),
+                        });
+                    }
+                }
+                if let Some(mt) = world.multitile_at(&tile_pos_at((x, y))) {
+                    if mt.pos.raw == (x, y) {
+                        multitiles.push(BlueprintMultiTile {
+                            x: x - x1,
+                            y: y - y1,
+                            mt_id: mt.mt_id as u32,
+                            extent: mt.extent,
+                        });
+                    }
+                }
+            }
+        }
+
+        Self {
+            name,
+            width: x2 - x1 + 1,
+            height: y2 - y1 + 1,
+            tiles,
+            multitiles,
+        }
+    }
+
+    /// Rotates the blueprint by a quarter turn clockwise, together with the orientation of every tile.
+    pub fn rotate_cw(&mut self) {
+        let width = self.width;
+        for tile in &mut self.tiles {
+            (tile.x, tile.y) = (tile.y, width - 1 - tile.x);
+            tile.orientation = orientation_to_u8(rotate_orientation_cw(tile.orientation()));
+        }
+        for mt in &mut self.multitiles {
+            (mt.x, mt.y) = (mt.y, width - mt.x - mt.extent.0);
+            mt.extent = (mt.extent.1, mt.extent.0);
+        }
+        (self.width, self.height) = (self.height, self.width);
+    }
+
+    pub fn rotate_ccw(&mut self) {
+        for _ in 0..3 {
+            self.rotate_cw();
+        }
+    }
+
+    /// Every tile with its raw position when the bottom left corner is placed at `origin`.
+    pub fn placed_tiles(&self, origin: (i32, i32)) -> Vec<((i32, i32), TileKind, Orientation)> {
+        self.tiles
+            .iter()
+            .map(|t| ((origin.0 + t.x, origin.1 + t.y), t.id, t.orientation()))
+            .collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = ByteBuffer::new();
+        buffer.write_u32(BLUEPRINT_VERSION);
+        self.save(&mut buffer);
+        buffer.into_vec()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut buffer = ByteBuffer::from_bytes(bytes);
+        let version = buffer.read_u32().map_err(|e| e.to_string())?;
+        match version {
+            1 => Blueprint::load(&mut buffer),
+            v => Err(format!("Unsupported blueprint version {v}")),
+        }
+    }
+
+    /// Text form of the blueprint that can be pasted in chat or sent around.
+    pub fn export_text(&self) -> String {
+        let hex = self
+            .to_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        format!("{TEXT_PREFIX}{BLUEPRINT_VERSION}:{hex}")
+    }
+
+    pub fn import_text(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let rest = text
+            .strip_prefix(TEXT_PREFIX)
+            .ok_or("Not a blueprint".to_string())?;
+        let (_, hex) = rest.split_once(':').ok_or("Missing blueprint data".to_string())?;
+        if hex.len() % 2 != 0 {
+            return Err("Blueprint data has an odd length".to_string());
+        }
+        //work on the raw bytes, slicing the str would panic inside multi byte characters
+        let bytes = hex
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or("Blueprint data is not hex".to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Blueprints saved under the configuration directory. Every blueprint is stored in its binary form,
+/// and additionally exported as text next to it so it can be shared. Text files dropped into the
+/// directory by hand are picked up as well.
+pub struct BlueprintLibrary {
+    dir: SmartDir,
+}
+
+impl BlueprintLibrary {
+    pub fn new(conf_dir: &SmartDir) -> Self {
+        Self {
+            dir: conf_dir.join(BLUEPRINT_DIR),
+        }
+    }
+
+    fn file(&self, name: &str, ext: &str) -> PathBuf {
+        let mut path = self.dir.path().to_path_buf();
+        path.push(format!("{}.{ext}", sanitize_file_name(name)));
+        path
+    }
+
+    pub fn save(&self, blueprint: &Blueprint) {
+        if let Err(e) = fs::create_dir_all(self.dir.path()) {
+            error!("Cannot create blueprint directory: {e}");
+            return;
+        }
+        let binary = fs::write(self.file(&blueprint.name, FILE_EXT), blueprint.to_bytes());
+        let text = fs::write(
+            self.file(&blueprint.name, TEXT_EXT),
+            blueprint.export_text(),
+        );
+        if let Err(e) = binary.and(text) {
+            error!("Cannot save blueprint {}: {e}", blueprint.name);
+        } else {
+            debug!("Saved blueprint {}", blueprint.name);
+        }
+    }
+
+    /// First `blueprint_<n>` that no file in the library uses yet.
+    pub fn unused_name(&self) -> String {
+        (1..)
+            .map(|i| format!("blueprint_{i}"))
+            .find(|name| {
+                !self.file(name, FILE_EXT).exists() && !self.file(name, TEXT_EXT).exists()
+            })
+            .unwrap()
+    }
+
+    /// Every blueprint in the library, sorted by name.
+    pub fn load_all(&self) -> Vec<Blueprint> {
+        let Ok(entries) = fs::read_dir(self.dir.path()) else {
+            return vec![];
+        };
+        let mut blueprints: Vec<Blueprint> = vec![];
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let loaded = match path.extension().and_then(|e| e.to_str()) {
+                Some(FILE_EXT) => fs::read(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| Blueprint::from_bytes(&bytes)),
+                Some(TEXT_EXT) => fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| Blueprint::import_text(&text)),
+                _ => continue,
+            };
+            match loaded {
+                //the text export of a saved blueprint is found as well
+                Ok(bp) if !blueprints.iter().any(|b| b.name == bp.name) => blueprints.push(bp),
+                Ok(_) => {}
+                Err(e) => warn!("Skipping blueprint {}: {e}", path.display()),
+            }
+        }
+        blueprints.sort_by(|a, b| a.name.cmp(&b.name));
+        blueprints
+    }
+}
+
+pub(crate) fn orientation_to_u8(orientation: Orientation) -> u8 {
+    match orientation {
+        Orientation::North => 0,
+        Orientation::East => 1,
+        Orientation::South => 2,
+        Orientation::West => 3,
+    }
+}
+
+pub(crate) fn orientation_from_u8(value: u8) -> Orientation {
+    match value % 4 {
+        0 => Orientation::North,
+        1 => Orientation::East,
+        2 => Orientation::South,
+        _ => Orientation::West,
+    }
+}
+
+fn rotate_orientation_cw(orientation: Orientation) -> Orientation {
+    orientation_from_u8(orientation_to_u8(orientation) + 1)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BlueprintMode {
+    Off,
+    /// Dragging with the mouse selects the area to copy.
+    Select,
+    /// The held blueprint follows the cursor and clicking pastes it.
+    Paste,
+}
+
+/// What the blueprint tool shows under the cursor.
+pub enum BlueprintPreview<'a> {
+    None,
+    Area((i32, i32), (i32, i32)),
+    Blueprint((i32, i32), &'a Blueprint),
+}
+
+pub struct BlueprintTool {
+    pub mode: BlueprintMode,
+    selection_start: Option<(i32, i32)>,
+    held: Option<Blueprint>,
+    library: BlueprintLibrary,
+    library_index: usize,
+}
+
+impl BlueprintTool {
+    pub fn new(conf_dir: &SmartDir) -> Self {
+        Self {
+            mode: BlueprintMode::Off,
+            selection_start: None,
+            held: None,
+            library: BlueprintLibrary::new(conf_dir),
+            library_index: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.mode != BlueprintMode::Off
+    }
+
+    pub fn is_pasting(&self) -> bool {
+        self.mode == BlueprintMode::Paste && self.held.is_some()
+    }
+
+    pub fn toggle(&mut self) {
+        self.mode = match self.mode {
+            BlueprintMode::Off => BlueprintMode::Select,
+            _ => BlueprintMode::Off,
+        };
+        self.selection_start = None;
+    }
+
+    pub fn close(&mut self) {
+        self.mode = BlueprintMode::Off;
+        self.selection_start = None;
+    }
+
+    /// Bottom left corner of the held blueprint when it is centered on the cursor.
+    fn origin(&self, cursor: (i32, i32)) -> (i32, i32) {
+        match &self.held {
+            Some(bp) => (cursor.0 - bp.width / 2, cursor.1 - bp.height / 2),
+            None => cursor,
+        }
+    }
+
+    /// Mouse pressed over the world, returns the tiles to set if a blueprint got pasted.
+    pub fn press(
+        &mut self,
+        cursor: (i32, i32),
+    ) -> Option<Vec<((i32, i32), TileKind, Orientation)>> {
+        match self.mode {
+            BlueprintMode::Select => {
+                self.selection_start = Some(cursor);
+                None
+            }
+            BlueprintMode::Paste => {
+                let bp = self.held.as_ref()?;
+                if !bp.multitiles.is_empty() {
+                    //there is no packet to place multitiles from the client yet
+                    warn!(
+                        "Skipping {} multitiles of blueprint {}",
+                        bp.multitiles.len(),
+                        bp.name
+                    );
+                }
+                Some(bp.placed_tiles(self.origin(cursor)))
+            }
+            BlueprintMode::Off => None,
+        }
+    }
+
+    /// Mouse released over the world, finishes a selection.
+    pub fn release(&mut self, cursor: (i32, i32), world: &ClientWorld) {
+        if self.mode != BlueprintMode::Select {
+            return;
+        }
+        let Some(start) = self.selection_start.take() else {
+            return;
+        };
+        let name = self.library.unused_name();
+        self.held = Some(Blueprint::capture(world, start, cursor, name));
+        self.mode = BlueprintMode::Paste;
+    }
+
+    pub fn rotate(&mut self, clockwise: bool) {
+        if let Some(bp) = &mut self.held {
+            if clockwise {
+                bp.rotate_cw();
+            } else {
+                bp.rotate_ccw();
+            }
+        }
+    }
+
+    pub fn save_held(&self) {
+        if let Some(bp) = &self.held {
+            self.library.save(bp);
+        }
+    }
+
+    /// Picks up the next blueprint of the library.
+    pub fn cycle_library(&mut self) {
+        let mut all = self.library.load_all();
+        if all.is_empty() {
+            warn!("The blueprint library is empty");
+            return;
+        }
+        let index = self.library_index % all.len();
+        self.library_index = index + 1;
+        self.held = Some(all.swap_remove(index));
+        self.mode = BlueprintMode::Paste;
+    }
+
+    pub fn preview(&self, cursor: (i32, i32)) -> BlueprintPreview<'_> {
+        match self.mode {
+            BlueprintMode::Select => match self.selection_start {
+                Some(start) => BlueprintPreview::Area(start, cursor),
+                None => BlueprintPreview::Area(cursor, cursor),
+            },
+            BlueprintMode::Paste => match &self.held {
+                Some(bp) => BlueprintPreview::Blueprint(self.origin(cursor), bp),
+                None => BlueprintPreview::None,
+            },
+            BlueprintMode::Off => BlueprintPreview::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blueprint() -> Blueprint {
+        Blueprint {
+            name: "test".to_string(),
+            width: 3,
+            height: 2,
+            tiles: vec![
+                BlueprintTile {
+                    x: 0,
+                    y: 0,
+                    id: 1,
+                    orientation: 0,
+                },
+                BlueprintTile {
+                    x: 2,
+                    y: 1,
+                    id: 4,
+                    orientation: 3,
+                },
+            ],
+            multitiles: vec![],
+        }
+    }
+
+    #[test]
+    fn exported_text_imports_again() {
+        let original = blueprint();
+        let imported = Blueprint::import_text(&original.export_text()).unwrap();
+        assert_eq!(imported.name, original.name);
+        assert_eq!((imported.width, imported.height), (3, 2));
+        let tiles: Vec<_> = imported
+            .tiles
+            .iter()
+            .map(|t| (t.x, t.y, t.id, t.orientation))
+            .collect();
+        assert_eq!(tiles, vec![(0, 0, 1, 0), (2, 1, 4, 3)]);
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        let text = format!("  {}\n", blueprint().export_text());
+        assert!(Blueprint::import_text(&text).is_ok());
+    }
+
+    #[test]
+    fn text_without_prefix_is_rejected() {
+        assert!(Blueprint::import_text("hello").is_err());
+        assert!(Blueprint::import_text("FIBP1").is_err());
+    }
+
+    #[test]
+    fn broken_hex_is_rejected() {
+        assert!(Blueprint::import_text("FIBP1:abc").is_err());
+        assert!(Blueprint::import_text("FIBP1:zz").is_err());
+    }
+
+    #[test]
+    fn non_ascii_data_is_rejected_without_panicking() {
+        assert!(Blueprint::import_text("FIBP1:ää").is_err());
+        assert!(Blueprint::import_text("FIBP1:aä0").is_err());
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut buffer = ByteBuffer::new();
+        buffer.write_u32(BLUEPRINT_VERSION + 1);
+        blueprint().save(&mut buffer);
+        assert!(Blueprint::from_bytes(&buffer.into_vec()).is_err());
+    }
+}
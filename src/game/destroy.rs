@@ -0,0 +1,221 @@
+use api::player::uuid::UUID;
+
+/// Seconds the destroy action has to be held before anything starts breaking,
+/// shorter presses count as clicks so interacting can share the button.
+pub const HOLD_DELAY: f64 = 0.2;
+/// Seconds it takes to break a tile once breaking started.
+pub const BREAK_TIME: f64 = 0.6;
+/// Seconds it takes to break a multitile once breaking started.
+pub const MULTITILE_BREAK_TIME: f64 = 1.5;
+/// Seconds a broken target waits for the confirming second break before it has to be broken twice again.
+pub const CONFIRM_TIMEOUT: f64 = 3.0;
+
+#[derive(Clone, PartialEq)]
+pub enum DestroyTarget {
+    Tile((i32, i32)),
+    MultiTile(UUID),
+}
+
+impl DestroyTarget {
+    fn break_time(&self) -> f64 {
+        match self {
+            DestroyTarget::Tile(_) => BREAK_TIME,
+            DestroyTarget::MultiTile(_) => MULTITILE_BREAK_TIME,
+        }
+    }
+}
+
+/// Hold to break. Progress resets whenever the target under the cursor changes.
+/// Moving off the tile the press started on turns it into an area removal instead,
+/// which is a removal drag started from that tile.
+pub struct DestroyTool {
+    active: bool,
+    /// Tile the press started on.
+    origin: Option<(i32, i32)>,
+    area: bool,
+    target: Option<DestroyTarget>,
+    held: f64,
+    /// Target that finished breaking once and waits for a second hold to confirm,
+    /// with the seconds it has been waiting.
+    armed: Option<(DestroyTarget, f64)>,
+}
+
+impl DestroyTool {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            origin: None,
+            area: false,
+            target: None,
+            held: 0.0,
+            armed: None,
+        }
+    }
+
+    pub fn start(&mut self, raw: (i32, i32)) {
+        self.active = true;
+        self.origin = Some(raw);
+        self.area = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Follows the cursor while held, returns whether the press turned into an area removal.
+    pub fn track(&mut self, raw: (i32, i32)) -> bool {
+        if self.active && !self.area && self.origin.is_some_and(|origin| origin != raw) {
+            self.area = true;
+            self.target = None;
+            self.held = 0.0;
+        }
+        self.area
+    }
+
+    /// Tile the press started on.
+    pub fn origin(&self) -> Option<(i32, i32)> {
+        self.origin
+    }
+
+    /// Ends the press, returns whether it was an area removal.
+    pub fn stop(&mut self) -> bool {
+        let area = self.active && self.area;
+        self.active = false;
+        self.origin = None;
+        self.area = false;
+        self.target = None;
+        self.held = 0.0;
+        area
+    }
+
+    /// Called every frame with the target under the cursor. A waiting confirmation is dropped
+    /// once the cursor leaves its target or after [`CONFIRM_TIMEOUT`].
+    pub fn update(&mut self, hovered: Option<&DestroyTarget>, dt: f64) {
+        let Some((target, waited)) = &mut self.armed else {
+            return;
+        };
+        *waited += dt;
+        if hovered != Some(&*target) || *waited > CONFIRM_TIMEOUT {
+            self.armed = None;
+        }
+    }
+
+    /// Advances breaking `target` and returns it once it broke.
+    pub fn hold(&mut self, target: Option<DestroyTarget>, dt: f64) -> Option<DestroyTarget> {
+        if target != self.target {
+            self.target = target;
+            self.held = 0.0;
+        }
+        let time = self.target.as_ref()?.break_time();
+        self.held += dt;
+        if self.held < HOLD_DELAY + time {
+            return None;
+        }
+        self.held = 0.0;
+        self.target.take()
+    }
+
+    /// Whether the press has been held or moved far enough to no longer count as a click.
+    pub fn is_breaking(&self) -> bool {
+        self.active && (self.area || (self.target.is_some() && self.held >= HOLD_DELAY))
+    }
+
+    /// Current target and how far it is broken, from 0 to 1.
+    pub fn progress(&self) -> Option<(&DestroyTarget, f32)> {
+        if !self.is_breaking() {
+            return None;
+        }
+        //an area removal has no target
+        let target = self.target.as_ref()?;
+        let progress = (self.held - HOLD_DELAY) / target.break_time();
+        Some((target, progress.clamp(0.0, 1.0) as f32))
+    }
+
+    /// True if `target` already broke once before, otherwise remembers it for the next time.
+    pub fn confirm(&mut self, target: &DestroyTarget) -> bool {
+        if self.armed.as_ref().is_some_and(|(armed, _)| armed == target) {
+            self.armed = None;
+            return true;
+        }
+        self.armed = Some((target.clone(), 0.0));
+        false
+    }
+
+    pub fn awaiting_confirmation(&self) -> bool {
+        self.armed.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f64 = 0.05;
+
+    /// Holds the action on `target` until it breaks, false if it never does.
+    fn break_target(tool: &mut DestroyTool, target: &DestroyTarget) -> bool {
+        tool.start((0, 0));
+        let broke = (0..1000).any(|_| tool.hold(Some(target.clone()), FRAME).is_some());
+        tool.stop();
+        broke
+    }
+
+    #[test]
+    fn short_press_is_no_break() {
+        let mut tool = DestroyTool::new();
+        let target = DestroyTarget::Tile((0, 0));
+        tool.start((0, 0));
+        assert!(tool.hold(Some(target), HOLD_DELAY / 2.0).is_none());
+        assert!(!tool.is_breaking());
+        assert!(!tool.stop());
+    }
+
+    #[test]
+    fn moving_off_the_origin_turns_into_an_area() {
+        let mut tool = DestroyTool::new();
+        tool.start((0, 0));
+        assert!(!tool.track((0, 0)));
+        assert!(tool.track((1, 0)));
+        assert!(tool.is_breaking());
+        assert_eq!(tool.origin(), Some((0, 0)));
+        assert!(tool.stop());
+    }
+
+    #[test]
+    fn second_break_confirms() {
+        let mut tool = DestroyTool::new();
+        let target = DestroyTarget::Tile((0, 0));
+        assert!(break_target(&mut tool, &target));
+        assert!(!tool.confirm(&target));
+        assert!(tool.awaiting_confirmation());
+        tool.update(Some(&target), FRAME);
+        assert!(tool.awaiting_confirmation());
+        assert!(break_target(&mut tool, &target));
+        assert!(tool.confirm(&target));
+        assert!(!tool.awaiting_confirmation());
+    }
+
+    #[test]
+    fn confirmation_is_dropped_when_the_cursor_moves_on() {
+        let mut tool = DestroyTool::new();
+        let target = DestroyTarget::Tile((0, 0));
+        assert!(!tool.confirm(&target));
+        tool.update(Some(&DestroyTarget::Tile((1, 0))), FRAME);
+        assert!(!tool.awaiting_confirmation());
+        assert!(!tool.confirm(&target));
+        tool.update(None, FRAME);
+        assert!(!tool.awaiting_confirmation());
+    }
+
+    #[test]
+    fn confirmation_times_out() {
+        let mut tool = DestroyTool::new();
+        let target = DestroyTarget::Tile((0, 0));
+        assert!(!tool.confirm(&target));
+        tool.update(Some(&target), CONFIRM_TIMEOUT / 2.0);
+        assert!(tool.awaiting_confirmation());
+        tool.update(Some(&target), CONFIRM_TIMEOUT);
+        assert!(!tool.awaiting_confirmation());
+        assert!(!tool.confirm(&target));
+    }
+}
@@ -0,0 +1,127 @@
+use api::world::tiles::Orientation;
+
+/// Most tiles a single drag can place or remove.
+pub const MAX_DRAG_TILES: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DragShape {
+    /// Straight line along the longer axis of the drag, tiles face the direction of the drag.
+    Line,
+    Rect,
+    HollowRect,
+}
+
+impl DragShape {
+    pub fn next(self) -> Self {
+        match self {
+            DragShape::Line => DragShape::Rect,
+            DragShape::Rect => DragShape::HollowRect,
+            DragShape::HollowRect => DragShape::Line,
+        }
+    }
+}
+
+/// Click and drag placement. The drag starts on mouse press and every tile of the shape
+/// between the start and the tile under the cursor is set at once on release.
+/// A drag started without a tile selected removes the tiles instead.
+pub struct DragBuild {
+    pub shape: DragShape,
+    start: Option<(i32, i32)>,
+    removing: bool,
+}
+
+impl DragBuild {
+    pub fn new() -> Self {
+        Self {
+            shape: DragShape::Line,
+            start: None,
+            removing: false,
+        }
+    }
+
+    pub fn start(&mut self, raw: (i32, i32)) {
+        self.start = Some(raw);
+        self.removing = false;
+    }
+
+    pub fn start_removal(&mut self, raw: (i32, i32)) {
+        self.start = Some(raw);
+        self.removing = true;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub fn is_removing(&self) -> bool {
+        self.is_dragging() && self.removing
+    }
+
+    pub fn cancel(&mut self) {
+        self.start = None;
+    }
+
+    /// Ends the drag and returns every tile of the shape with the orientation it should be placed in.
+    pub fn finish(
+        &mut self,
+        end: (i32, i32),
+        orientation: Orientation,
+    ) -> Vec<((i32, i32), Orientation)> {
+        let tiles = self.tiles(end, orientation);
+        self.start = None;
+        tiles
+    }
+
+    /// Tiles the drag would cover if it ended at `end`, empty if there is no drag.
+    pub fn tiles(
+        &self,
+        end: (i32, i32),
+        orientation: Orientation,
+    ) -> Vec<((i32, i32), Orientation)> {
+        let Some(start) = self.start else {
+            return vec![];
+        };
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let mut tiles = vec![];
+        match self.shape {
+            DragShape::Line => {
+                let (step, len, facing) = if dx.abs() >= dy.abs() {
+                    let facing = if dx >= 0 {
+                        Orientation::East
+                    } else {
+                        Orientation::West
+                    };
+                    ((dx.signum(), 0), dx.abs(), facing)
+                } else {
+                    let facing = if dy > 0 {
+                        Orientation::North
+                    } else {
+                        Orientation::South
+                    };
+                    ((0, dy.signum()), dy.abs(), facing)
+                };
+                //a single tile keeps the orientation the player picked
+                let facing = if len == 0 { orientation } else { facing };
+                for i in 0..=len.min(MAX_DRAG_TILES as i32 - 1) {
+                    tiles.push(((start.0 + step.0 * i, start.1 + step.1 * i), facing));
+                }
+            }
+            DragShape::Rect | DragShape::HollowRect => {
+                let (x1, x2) = (start.0.min(end.0), start.0.max(end.0));
+                let (y1, y2) = (start.1.min(end.1), start.1.max(end.1));
+                'outer: for x in x1..=x2 {
+                    for y in y1..=y2 {
+                        if tiles.len() >= MAX_DRAG_TILES {
+                            break 'outer;
+                        }
+                        let edge = x == x1 || x == x2 || y == y1 || y == y2;
+                        if self.shape == DragShape::Rect || edge {
+                            tiles.push(((x, y), orientation));
+                        }
+                    }
+                }
+            }
+        }
+        tiles
+    }
+}
@@ -0,0 +1,185 @@
+use crate::world::{tile_pos_at, ClientWorld};
+use api::world::tiles::{Orientation, TileKind};
+
+/// Most building actions that can be undone.
+pub const MAX_HISTORY: usize = 100;
+
+/// A single tile this client set, together with what was there before.
+#[derive(Clone, Copy)]
+pub struct TileChange {
+    pub raw: (i32, i32),
+    pub before: (TileKind, Orientation),
+    pub after: (TileKind, Orientation),
+}
+
+impl TileChange {
+    fn inverse(&self) -> Self {
+        Self {
+            raw: self.raw,
+            before: self.after,
+            after: self.before,
+        }
+    }
+
+    /// Whether the world still holds what this change replaces. Unloaded chunks never match.
+    pub fn applies_to(&self, world: &ClientWorld) -> bool {
+        if world.get_chunk(tile_pos_at(self.raw).chunk_pos).is_none() {
+            return false;
+        }
+        match world.tile_at(self.raw) {
+            Some(tile) => tile.id == self.before.0 && tile.orientation == self.before.1,
+            None => self.before.0 == 0,
+        }
+    }
+}
+
+/// Every tile one building action (a click, a drag or a paste) changed.
+pub type HistoryEntry = Vec<TileChange>;
+
+/// Undo and redo stacks of building actions. Undoing sends the inverse tile sets,
+/// so the history only ever contains changes this client requested itself.
+pub struct BuildHistory {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+impl BuildHistory {
+    pub fn new() -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+
+    /// Records a new action, which makes everything undone so far unredoable.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        if entry.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.push_undo(entry);
+    }
+
+    fn push_undo(&mut self, entry: HistoryEntry) {
+        if self.undo.len() >= MAX_HISTORY {
+            self.undo.remove(0);
+        }
+        self.undo.push(entry);
+    }
+
+    /// Takes the latest action and returns the changes reverting it.
+    pub fn take_undo(&mut self) -> Option<HistoryEntry> {
+        self.undo
+            .pop()
+            .map(|entry| entry.iter().rev().map(TileChange::inverse).collect())
+    }
+
+    /// Takes the latest undone action and returns the changes reapplying it.
+    pub fn take_redo(&mut self) -> Option<HistoryEntry> {
+        self.redo
+            .pop()
+            .map(|entry| entry.iter().rev().map(TileChange::inverse).collect())
+    }
+
+    /// Stores the changes an undo actually sent, so they can be redone.
+    pub fn undone(&mut self, applied: HistoryEntry) {
+        if !applied.is_empty() {
+            self.redo.push(applied);
+        }
+    }
+
+    /// Stores the changes a redo actually sent, without dropping the remaining redo stack.
+    pub fn redone(&mut self, applied: HistoryEntry) {
+        if !applied.is_empty() {
+            self.push_undo(applied);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(raw: (i32, i32), before: TileKind, after: TileKind) -> TileChange {
+        TileChange {
+            raw,
+            before: (before, Orientation::North),
+            after: (after, Orientation::East),
+        }
+    }
+
+    fn summary(entry: &HistoryEntry) -> Vec<((i32, i32), TileKind, TileKind)> {
+        entry.iter().map(|c| (c.raw, c.before.0, c.after.0)).collect()
+    }
+
+    #[test]
+    fn undo_inverts_the_latest_action_in_reverse_order() {
+        let mut history = BuildHistory::new();
+        history.record(vec![change((0, 0), 0, 1)]);
+        history.record(vec![change((1, 0), 0, 2), change((2, 0), 3, 2)]);
+
+        let undo = history.take_undo().unwrap();
+        assert_eq!(summary(&undo), vec![((2, 0), 2, 3), ((1, 0), 2, 0)]);
+        assert!(undo.iter().all(|c| c.after.1 == Orientation::North));
+
+        let undo = history.take_undo().unwrap();
+        assert_eq!(summary(&undo), vec![((0, 0), 1, 0)]);
+        assert!(history.take_undo().is_none());
+    }
+
+    #[test]
+    fn redo_reapplies_what_was_undone() {
+        let mut history = BuildHistory::new();
+        let action = vec![change((0, 0), 0, 1), change((1, 0), 0, 1)];
+        history.record(action.clone());
+
+        let undo = history.take_undo().unwrap();
+        history.undone(undo);
+        let redo = history.take_redo().unwrap();
+        assert_eq!(summary(&redo), summary(&action));
+
+        history.redone(redo);
+        assert!(history.take_redo().is_none());
+        let undo = history.take_undo().unwrap();
+        assert_eq!(summary(&undo), vec![((1, 0), 1, 0), ((0, 0), 1, 0)]);
+    }
+
+    #[test]
+    fn recording_drops_the_redo_stack() {
+        let mut history = BuildHistory::new();
+        history.record(vec![change((0, 0), 0, 1)]);
+        let undo = history.take_undo().unwrap();
+        history.undone(undo);
+
+        history.record(vec![change((5, 5), 0, 1)]);
+        assert!(history.take_redo().is_none());
+    }
+
+    #[test]
+    fn empty_actions_are_not_recorded() {
+        let mut history = BuildHistory::new();
+        history.record(vec![]);
+        assert!(history.take_undo().is_none());
+
+        history.record(vec![change((0, 0), 0, 1)]);
+        history.take_undo();
+        history.undone(vec![]);
+        assert!(history.take_redo().is_none());
+    }
+
+    #[test]
+    fn only_the_latest_actions_are_kept() {
+        let mut history = BuildHistory::new();
+        for i in 0..=MAX_HISTORY as i32 {
+            history.record(vec![change((i, 0), 0, 1)]);
+        }
+        let mut count = 0;
+        let mut oldest = None;
+        while let Some(undo) = history.take_undo() {
+            oldest = Some(undo[0].raw);
+            count += 1;
+        }
+        assert_eq!(count, MAX_HISTORY);
+        assert_eq!(oldest, Some((1, 0)));
+    }
+}
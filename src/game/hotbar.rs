@@ -0,0 +1,70 @@
+use crate::game::blueprint::{orientation_from_u8, orientation_to_u8};
+use api::world::tiles::{Orientation, TileKind};
+use mvutils::Savable;
+
+pub const HOTBAR_SLOTS: usize = 9;
+
+/// Hotbar and selection of one server, as stored in the persistent data.
+#[derive(Clone, Savable)]
+pub struct SavedHotbar {
+    pub server: String,
+    pub slots: Vec<TileKind>,
+    /// 0 if no tile was selected.
+    pub selected: TileKind,
+    orientation: u8,
+}
+
+/// Tile kinds bound to the number keys, 0 marks an empty slot.
+pub struct Hotbar {
+    slots: [TileKind; HOTBAR_SLOTS],
+}
+
+impl Hotbar {
+    pub fn new() -> Self {
+        Self {
+            slots: [0; HOTBAR_SLOTS],
+        }
+    }
+
+    pub fn from_saved(saved: &SavedHotbar) -> Self {
+        let mut this = Self::new();
+        for (slot, kind) in this.slots.iter_mut().zip(&saved.slots) {
+            *slot = *kind;
+        }
+        this
+    }
+
+    pub fn to_saved(
+        &self,
+        server: String,
+        selected: Option<TileKind>,
+        orientation: Orientation,
+    ) -> SavedHotbar {
+        SavedHotbar {
+            server,
+            slots: self.slots.to_vec(),
+            selected: selected.unwrap_or(0),
+            orientation: orientation_to_u8(orientation),
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<TileKind> {
+        self.slots.get(slot).copied().filter(|kind| *kind != 0)
+    }
+
+    pub fn assign(&mut self, slot: usize, kind: Option<TileKind>) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            *s = kind.unwrap_or(0);
+        }
+    }
+}
+
+impl SavedHotbar {
+    pub fn selected(&self) -> Option<TileKind> {
+        (self.selected != 0).then_some(self.selected)
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        orientation_from_u8(self.orientation)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::world::terrain::terrain_for;
+use crate::world::tiles::impls::tile_template;
+use crate::world::ClientWorld;
+use api::world::tiles::pos::TilePos;
+
+/// Everything the client knows about a tile, one line per fact.
+pub fn inspect(world: &ClientWorld, pos: &TilePos) -> Vec<String> {
+    let mut lines = vec![
+        format!("Tile {}, {}", pos.raw.0, pos.raw.1),
+        format!("Chunk {}, {}", pos.chunk_pos.0, pos.chunk_pos.1),
+    ];
+
+    if world.get_chunk(pos.chunk_pos).is_none() {
+        lines.push("Chunk not loaded".to_string());
+        return lines;
+    }
+
+    match world.terrain_at(pos.raw) {
+        Some(id) => {
+            let terrain = terrain_for(id);
+            let name = terrain.map_or_else(|| format!("Terrain #{id}"), |t| t.label());
+            let walkable = terrain.map_or(true, |t| t.walkable);
+            lines.push(format!(
+                "{name}{}",
+                if walkable { "" } else { " (not walkable)" }
+            ));
+        }
+        None => lines.push("Terrain: void".to_string()),
+    }
+
+    if let Some(tile) = world.tile_at(pos.raw) {
+        let name = tile_template(tile.id)
+            .map(|t| t.name)
+            .filter(|name| !name.is_empty())
+            .unwrap_or("unknown");
+        lines.push(format!("Tile #{} {name}, facing {:?}", tile.id, tile.orientation));
+        if let Some(state) = &tile.state {
+            lines.extend(state.describe());
+        }
+    }
+
+    if let Some(placement) = world.multitile_at(pos) {
+        lines.push(format!("Multitile #{} {:?}", placement.mt_id, placement.uuid));
+        lines.push(format!(
+            "Origin {}, {}, extent {}x{}",
+            placement.pos.raw.0, placement.pos.raw.1, placement.extent.0, placement.extent.1
+        ));
+    }
+
+    lines
+}
@@ -0,0 +1,126 @@
+use crate::drawutils;
+use crate::drawutils::Fill;
+use crate::player::ClientPlayer;
+use crate::world::map::MapChunk;
+use api::world::{ChunkPos, CHUNK_SIZE};
+use mvengine::color::RgbColor;
+use mvengine::net::server::ClientId;
+use mvengine::rendering::RenderContext;
+use mvengine::ui::rendering::WideRenderContext;
+use mvutils::hashers::U64IdentityHasher;
+use std::collections::HashMap;
+
+/// Side length of the minimap in pixels.
+pub const MINIMAP_SIZE: i32 = 200;
+/// Pixels per tile on the minimap.
+pub const MINIMAP_SCALE: i32 = 2;
+const MARGIN: i32 = 10;
+
+/// Small map in the top right corner showing every loaded chunk around the player.
+pub struct Minimap {
+    chunks: HashMap<ChunkPos, MapChunk>,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self {
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, pos: ChunkPos, chunk: MapChunk) {
+        self.chunks.insert(pos, chunk);
+    }
+
+    /// Updates one cell of a chunk, returns false if the chunk is not on the minimap yet.
+    pub fn set_cell(&mut self, pos: ChunkPos, cell_index: usize, cell: u16) -> bool {
+        match self.chunks.get_mut(&pos) {
+            Some(chunk) => {
+                chunk.set_cell(cell_index, cell);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_chunk(&mut self, pos: ChunkPos) {
+        self.chunks.remove(&pos);
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut impl WideRenderContext,
+        player: &ClientPlayer,
+        other_players: &HashMap<ClientId, ClientPlayer, U64IdentityHasher>,
+    ) {
+        let z = ctx.next_z();
+        let x0 = player.camera.width - MINIMAP_SIZE - MARGIN;
+        let y0 = player.camera.height - MINIMAP_SIZE - MARGIN;
+        drawutils::rect(
+            ctx,
+            x0,
+            y0,
+            MINIMAP_SIZE,
+            MINIMAP_SIZE,
+            Fill::Color(RgbColor::black().alpha(150)),
+            z,
+        );
+
+        let (cx, cy) = player.pos();
+        //screen position of a raw tile coordinate, the player is in the center
+        let to_screen = |tx: f64, ty: f64| {
+            (
+                x0 + MINIMAP_SIZE / 2 + ((tx - cx) * MINIMAP_SCALE as f64) as i32,
+                y0 + MINIMAP_SIZE / 2 + ((ty - cy) * MINIMAP_SCALE as f64) as i32,
+            )
+        };
+
+        for (pos, chunk) in &self.chunks {
+            let origin = (pos.0 * CHUNK_SIZE, pos.1 * CHUNK_SIZE);
+            let (sx, sy) = to_screen(origin.0 as f64, origin.1 as f64);
+            let chunk_px = CHUNK_SIZE * MINIMAP_SCALE;
+            if sx + chunk_px < x0
+                || sy + chunk_px < y0
+                || sx > x0 + MINIMAP_SIZE
+                || sy > y0 + MINIMAP_SIZE
+            {
+                continue;
+            }
+            for run in &chunk.runs {
+                let ry = sy + run.y * MINIMAP_SCALE;
+                if ry < y0 || ry + MINIMAP_SCALE > y0 + MINIMAP_SIZE {
+                    continue;
+                }
+                let rx1 = (sx + run.x * MINIMAP_SCALE).max(x0);
+                let rx2 = (sx + (run.x + run.len) * MINIMAP_SCALE).min(x0 + MINIMAP_SIZE);
+                if rx2 <= rx1 {
+                    continue;
+                }
+                drawutils::rect(
+                    ctx,
+                    rx1,
+                    ry,
+                    rx2 - rx1,
+                    MINIMAP_SCALE,
+                    Fill::Color(run.color.clone()),
+                    z,
+                );
+            }
+        }
+
+        for other in other_players.values() {
+            let (px, py) = other.pos();
+            let (sx, sy) = to_screen(px, py);
+            if sx >= x0 && sy >= y0 && sx < x0 + MINIMAP_SIZE && sy < y0 + MINIMAP_SIZE {
+                drawutils::rect(ctx, sx - 2, sy - 2, 5, 5, Fill::Color(RgbColor::red()), z);
+            }
+        }
+
+        let (sx, sy) = to_screen(cx, cy);
+        drawutils::rect(ctx, sx - 2, sy - 2, 5, 5, Fill::Color(RgbColor::yellow()), z);
+    }
+}
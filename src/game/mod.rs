@@ -1,5 +1,13 @@
+pub mod blueprint;
+pub mod destroy;
+pub mod drag_build;
+pub mod history;
+pub mod hotbar;
+pub mod inspector;
+pub mod minimap;
 pub mod persistent;
 pub mod place_tile;
+pub mod worldmap;
 pub mod worldview;
 
 use crate::game::persistent::{PersistentGameData, PersistentLoadedData, PERSISTENT_FILE};
@@ -51,6 +59,13 @@ use std::path::{Path, PathBuf};
 
 pub const INTERNAL_IP: &str = "127.0.0.1:4040";
 
+/// Replaces everything that might not be allowed in a file name, like the colon of a server address.
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
 pub struct Game {
     pub conf_dir: SmartDir,
     pub res_dir: SmartDir,
@@ -58,11 +73,13 @@ pub struct Game {
     pub world_view: Option<WorldView>,
     pub profile: PlayerProfile,
     pub is_internal: bool,
+    /// Address of the server we are connected to, used to keep data per server apart.
+    pub server_name: String,
     pub persistent_game_data: PersistentLoadedData,
 }
 
 impl Game {
-    pub fn new(is_internal: bool) -> Self {
+    pub fn new(is_internal: bool, world: Option<String>) -> Self {
         let appdata = env::var("APPDATA").expect("Failed to get APPDATA environment variable");
         let mut full = PathBuf::from(appdata);
         full.push(input::PATH);
@@ -83,6 +100,7 @@ impl Game {
             world_view: None,
             profile,
             is_internal,
+            server_name: format!("internal_{}", world.as_deref().unwrap_or("world")),
             persistent_game_data: persistent_game_data.to_loaded(),
         }
     }
@@ -95,7 +113,7 @@ impl Game {
     ) {
         if let Some(view) = &mut self.world_view {
             if let Some(client) = client {
-                view.on_frame(window, client, ui_manager);
+                view.on_frame(window, client, ui_manager, &self.settings);
             }
         }
     }
@@ -150,7 +168,17 @@ impl Game {
         }
     }
 
-    pub fn exit(&self) {
+    /// Saves the map and keeps the hotbar of the current server, call before leaving a world.
+    pub fn store_world_data(&mut self) {
+        if let Some(view) = &mut self.world_view {
+            view.world_map.save();
+            let hotbar = view.saved_hotbar(self.server_name.clone());
+            self.persistent_game_data.store_hotbar(hotbar);
+        }
+    }
+
+    pub fn exit(&mut self) {
+        self.store_world_data();
         self.save_settings();
     }
 }
@@ -1,35 +1,67 @@
+use crate::game::hotbar::SavedHotbar;
 use mvengine::ui::utils::ToRope;
+use mvutils::save::{Loader, Savable, Saver};
 use mvutils::state::State;
-use mvutils::Savable;
 use ropey::Rope;
 
 pub const PERSISTENT_FILE: &str = "persistent.sav";
 
-#[derive(Clone, Savable)]
+#[derive(Clone)]
 pub struct PersistentGameData {
     pub last_ip: String,
+    pub hotbars: Vec<SavedHotbar>,
 }
 
 impl PersistentGameData {
     pub fn new() -> Self {
         Self {
             last_ip: String::new(),
+            hotbars: vec![],
         }
     }
 
     pub fn to_loaded(self) -> PersistentLoadedData {
         PersistentLoadedData {
             last_ip: State::new(self.last_ip.to_rope()),
+            hotbars: self.hotbars,
         }
     }
 
     pub fn from_loaded(loaded: &PersistentLoadedData) -> Self {
         Self {
             last_ip: loaded.last_ip.read().to_string(),
+            hotbars: loaded.hotbars.clone(),
         }
     }
 }
 
+/// Files written before hotbars were stored only contain the last ip, which must survive the update.
+impl Savable for PersistentGameData {
+    fn save(&self, saver: &mut impl Saver) {
+        self.last_ip.save(saver);
+        self.hotbars.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+        Ok(Self {
+            last_ip: String::load(loader)?,
+            hotbars: Vec::load(loader).unwrap_or_default(),
+        })
+    }
+}
+
 pub struct PersistentLoadedData {
     pub last_ip: State<Rope>,
+    pub hotbars: Vec<SavedHotbar>,
+}
+
+impl PersistentLoadedData {
+    pub fn hotbar_for(&self, server: &str) -> Option<&SavedHotbar> {
+        self.hotbars.iter().find(|h| h.server == server)
+    }
+
+    pub fn store_hotbar(&mut self, hotbar: SavedHotbar) {
+        self.hotbars.retain(|h| h.server != hotbar.server);
+        self.hotbars.push(hotbar);
+    }
 }
@@ -1,62 +1,273 @@
 use crate::drawutils;
 use crate::drawutils::Fill;
+use crate::game::blueprint::BlueprintPreview;
+use crate::game::destroy::DestroyTarget;
 use crate::game::worldview::WorldView;
 use crate::gamesettings::GameSettings;
 use crate::res::R;
-use crate::world::tiles::impls::CLIENT_TILE_REG;
+use crate::player::ClientPlayer;
+use crate::world::terrain::terrain_for;
+use crate::world::{tile_pos_at, ClientWorld};
+use crate::world::tiles::impls::{tile_template, CLIENT_TILE_REG};
 use api::world::tiles::pos::TilePos;
-use api::world::{resolve_unit, SingleTileUnit};
+use api::world::{resolve_unit, SingleTileUnit, TileExtent};
 use mvengine::color::RgbColor;
 use mvengine::rendering::RenderContext;
 use mvengine::ui::context::UiResources;
 use mvengine::ui::geometry::shape::shapes;
 use mvengine::window::Window;
 
+/// Height of the break progress bar in tiles.
+const BREAK_BAR_HEIGHT: SingleTileUnit = 0.15;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+    OutOfReach,
+    NotLoaded,
+    Terrain,
+    MultiTile,
+    Occupied,
+}
+
+impl PlacementError {
+    pub fn reason(self) -> &'static str {
+        match self {
+            PlacementError::OutOfReach => "Out of reach",
+            PlacementError::NotLoaded => "Chunk not loaded",
+            PlacementError::Terrain => "Cannot build on this terrain",
+            PlacementError::MultiTile => "Occupied by a multitile",
+            PlacementError::Occupied => "Occupied by a tile",
+        }
+    }
+}
+
+/// Client side guess whether the server will accept a tile at `raw`, the server still has the final say.
+pub fn check_placement(
+    world: &ClientWorld,
+    player: &ClientPlayer,
+    raw: (i32, i32),
+) -> Result<(), PlacementError> {
+    let pos = tile_pos_at(raw);
+    if pos.distance_from(player) > player.reach {
+        return Err(PlacementError::OutOfReach);
+    }
+    let Some(terrain) = world.terrain_at(raw) else {
+        return Err(PlacementError::NotLoaded);
+    };
+    if !terrain_for(terrain).map_or(true, |t| t.buildable) {
+        return Err(PlacementError::Terrain);
+    }
+    if world.is_multitile_at(&pos) {
+        return Err(PlacementError::MultiTile);
+    }
+    if world.tile_at(raw).is_some() {
+        return Err(PlacementError::Occupied);
+    }
+    Ok(())
+}
+
+/// [`check_placement`] for every tile of a footprint starting at `origin`, the first problem wins.
+pub fn check_footprint(
+    world: &ClientWorld,
+    player: &ClientPlayer,
+    origin: (i32, i32),
+    extent: TileExtent,
+) -> Result<(), PlacementError> {
+    for x in origin.0..origin.0 + extent.0 {
+        for y in origin.1..origin.1 + extent.1 {
+            check_placement(world, player, (x, y))?;
+        }
+    }
+    Ok(())
+}
+
+fn validity_tint(result: Result<(), PlacementError>) -> RgbColor {
+    match result {
+        Ok(()) => RgbColor::green().alpha(100),
+        Err(_) => RgbColor::red().alpha(140),
+    }
+}
+
 pub fn draw_overlay(view: &mut WorldView, window: &Window, settings: &GameSettings) {
     let pipeline = &mut view.overlay_pipeline;
     let player = &view.player;
+    let world = &view.world;
     let tile_size = view.tile_size;
     let orientation = view.orientation;
 
-    if let Some(sel) = view.tile_selection.selected_tile() {
-        view.player_pipeline.next_pipeline(pipeline);
+    view.player_pipeline.next_pipeline(pipeline);
+
+    let tile = view
+        .tile_selection
+        .selected_tile()
+        .and_then(|sel| CLIENT_TILE_REG.reference_object(sel.saturating_sub(1) as usize));
 
-        if let Some(tile) = CLIENT_TILE_REG.reference_object(sel.saturating_sub(1) as usize) {
-            let mx = window.input.mouse_x;
-            let my = window.input.mouse_y;
-            let pos = TilePos::from_screen((mx, my), &player.camera.view_area, tile_size);
+    let mx = window.input.mouse_x;
+    let my = window.input.mouse_y;
+    let pos = TilePos::from_screen((mx, my), &player.camera.view_area, tile_size);
 
-            if *settings.indicator_circle.read() {
-                let (px, py) = drawutils::get_screen_pos(
+    if *settings.indicator_circle.read() {
+        let (px, py) =
+            drawutils::get_screen_pos(&player.camera.view_area, player.pos(), view.tile_size);
+        let reach = (player.reach * view.tile_size as SingleTileUnit) as i32;
+        let circle = shapes::circle0(px, py, reach, 30);
+        circle.draw(pipeline, |v| {
+            v.color = RgbColor::green().alpha(100).as_vec4();
+        });
+    }
+
+    let mut hint = None;
+    let y = pipeline.controller().next_z();
+    match view.blueprints.preview(pos.raw) {
+        BlueprintPreview::Area(a, b) => {
+            let min = (a.0.min(b.0), a.1.min(b.1));
+            let size = ((a.0 - b.0).abs() + 1, (a.1 - b.1).abs() + 1);
+            drawutils::draw_in_world(
+                pipeline,
+                &player.camera.view_area,
+                (min.0 as SingleTileUnit, min.1 as SingleTileUnit),
+                (size.0 as SingleTileUnit, size.1 as SingleTileUnit),
+                Fill::Color(RgbColor::white().alpha(60)),
+                tile_size,
+                y,
+            );
+        }
+        BlueprintPreview::Blueprint(origin, blueprint) => {
+            for (raw, id, orientation) in blueprint.placed_tiles(origin) {
+                let Some(template) = tile_template(id) else {
+                    continue;
+                };
+                let tint = validity_tint(check_placement(world, player, raw));
+                drawutils::draw_in_world_tile(
+                    pipeline,
                     &player.camera.view_area,
-                    player.pos(),
-                    view.tile_size,
+                    tile_pos_at(raw),
+                    Fill::TintedDrawable(template.base.clone(), orientation, tint),
+                    tile_size,
+                    y,
                 );
-                let reach = (player.reach * view.tile_size as SingleTileUnit) as i32;
-                let circle = shapes::circle0(px, py, reach, 30);
-                circle.draw(pipeline, |v| {
-                    v.color = RgbColor::green().alpha(100).as_vec4();
-                });
             }
-
-            if pos.distance_from(player) <= player.reach {
-                let y = pipeline.controller().next_z();
-                drawutils::draw_in_world_tile(
+            for mt in &blueprint.multitiles {
+                drawutils::draw_in_world(
                     pipeline,
                     &player.camera.view_area,
-                    pos,
-                    Fill::Drawable(tile.base.clone(), orientation),
+                    (
+                        (origin.0 + mt.x) as SingleTileUnit,
+                        (origin.1 + mt.y) as SingleTileUnit,
+                    ),
+                    (mt.extent.0 as SingleTileUnit, mt.extent.1 as SingleTileUnit),
+                    Fill::Color(RgbColor::yellow().alpha(60)),
                     tile_size,
                     y,
                 );
             }
         }
+        BlueprintPreview::None => {
+            if view.drag.is_removing() {
+                for (raw, _) in view.drag.tiles(pos.raw, orientation) {
+                    let pos = tile_pos_at(raw);
+                    if pos.distance_from(player) > player.reach {
+                        continue;
+                    }
+                    drawutils::draw_in_world_tile(
+                        pipeline,
+                        &player.camera.view_area,
+                        pos,
+                        Fill::Color(RgbColor::red().alpha(90)),
+                        tile_size,
+                        y,
+                    );
+                }
+            } else if let Some(tile) = &tile {
+                //while dragging, preview every tile the drag covers
+                let mut tiles = view.drag.tiles(pos.raw, orientation);
+                if tiles.is_empty() {
+                    tiles.push((pos.raw, orientation));
+                }
 
-        view.overlay_pipeline.advance(window, |_| {});
-        view.overlay_pipeline.advance(window, |s| {
-            s.uniform_1f("FRAME", view.frame as f32);
-            let noise_tex = R.resolve_texture(R.texture.noise).expect("It exists bro");
-            s.uniform_texture(noise_tex, "NOISE");
-        });
+                //tiles bigger than one tile preview their whole footprint, turned by the orientation
+                for (raw, orientation) in tiles {
+                    let extent = tile.extent(orientation);
+                    let tint = validity_tint(check_footprint(world, player, raw, extent));
+                    drawutils::draw_in_world(
+                        pipeline,
+                        &player.camera.view_area,
+                        (raw.0 as SingleTileUnit, raw.1 as SingleTileUnit),
+                        (extent.0 as SingleTileUnit, extent.1 as SingleTileUnit),
+                        Fill::TintedDrawable(tile.base.clone(), orientation, tint),
+                        tile_size,
+                        y,
+                    );
+                }
+
+                //an existing multitile blocks its whole footprint, so show all of it
+                if let Some(placement) = world.multitile_at(&pos) {
+                    drawutils::draw_in_world(
+                        pipeline,
+                        &player.camera.view_area,
+                        (
+                            placement.pos.raw.0 as SingleTileUnit,
+                            placement.pos.raw.1 as SingleTileUnit,
+                        ),
+                        (
+                            placement.extent.0 as SingleTileUnit,
+                            placement.extent.1 as SingleTileUnit,
+                        ),
+                        Fill::Color(RgbColor::red().alpha(60)),
+                        tile_size,
+                        y,
+                    );
+                }
+                hint = check_footprint(world, player, pos.raw, tile.extent(orientation))
+                    .err()
+                    .map(PlacementError::reason);
+            }
+        }
+    }
+
+    if let Some((target, progress)) = view.destroy.progress() {
+        let footprint = match target {
+            DestroyTarget::Tile(raw) => Some((*raw, (1, 1))),
+            DestroyTarget::MultiTile(uuid) => world
+                .multitile_at(&pos)
+                .filter(|placement| placement.uuid == *uuid)
+                .map(|placement| (placement.pos.raw, placement.extent)),
+        };
+        if let Some((origin, extent)) = footprint {
+            let origin = (origin.0 as SingleTileUnit, origin.1 as SingleTileUnit);
+            let extent = (extent.0 as SingleTileUnit, extent.1 as SingleTileUnit);
+            drawutils::draw_in_world(
+                pipeline,
+                &player.camera.view_area,
+                origin,
+                extent,
+                Fill::Color(RgbColor::black().alpha(100)),
+                tile_size,
+                y,
+            );
+            drawutils::draw_in_world(
+                pipeline,
+                &player.camera.view_area,
+                origin,
+                (extent.0 * progress as SingleTileUnit, BREAK_BAR_HEIGHT),
+                Fill::Color(RgbColor::white().alpha(200)),
+                tile_size,
+                y,
+            );
+        }
+    } else if view.destroy.awaiting_confirmation() {
+        hint = Some("Break again to confirm");
     }
+
+    match hint {
+        Some(reason) => view.placement_hint.show(vec![reason.to_string()], (mx, my)),
+        None => view.placement_hint.hide(),
+    }
+
+    view.overlay_pipeline.advance(window, |_| {});
+    view.overlay_pipeline.advance(window, |s| {
+        s.uniform_1f("FRAME", view.frame as f32);
+        let noise_tex = R.resolve_texture(R.texture.noise).expect("It exists bro");
+        s.uniform_texture(noise_tex, "NOISE");
+    });
 }
@@ -0,0 +1,243 @@
+use crate::drawutils;
+use crate::drawutils::Fill;
+use crate::game::{sanitize_file_name, Game};
+use crate::world::map::MapChunk;
+use api::world::chunk::CHUNK_TILES;
+use api::world::{ChunkPos, TileUnit, CHUNK_SIZE};
+use log::{debug, warn};
+use mvengine::color::RgbColor;
+use mvengine::game::fs::smartdir::SmartDir;
+use mvengine::rendering::RenderContext;
+use mvengine::ui::rendering::WideRenderContext;
+use mvutils::Savable;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub const MAPS_DIR: &str = "maps";
+
+/// Pixels per tile the world map can be zoomed between.
+pub const MIN_MAP_SCALE: f64 = 0.5;
+pub const MAX_MAP_SCALE: f64 = 16.0;
+const DEFAULT_MAP_SCALE: f64 = 4.0;
+/// How far away from the center (in tiles) a marker can be to get removed.
+const MARKER_PICK_RADIUS: f64 = 16.0;
+/// How often newly explored area is written to disk, so a crash loses at most this much.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Savable)]
+pub struct MapMarker {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Savable)]
+struct SavedMapChunk {
+    x: i32,
+    y: i32,
+    cells: Vec<u16>,
+}
+
+#[derive(Savable)]
+struct SavedWorldMap {
+    chunks: Vec<SavedMapChunk>,
+    markers: Vec<MapMarker>,
+}
+
+/// Every chunk this client has ever received on a server, kept even after the server unloads it.
+/// Stored per server in the maps directory together with the markers placed on it.
+pub struct WorldMap {
+    dir: SmartDir,
+    file: String,
+    chunks: HashMap<ChunkPos, MapChunk>,
+    pub markers: Vec<MapMarker>,
+    /// Raw tile coordinate in the middle of the screen.
+    pub center: TileUnit,
+    /// Pixels per tile.
+    pub scale: f64,
+    /// Whether something was explored since the last save.
+    unsaved: bool,
+    last_save: Instant,
+}
+
+impl WorldMap {
+    pub fn load(game: &Game) -> Self {
+        let dir = game.configuration_directory().join(MAPS_DIR);
+        let file = format!("{}.sav", sanitize_file_name(&game.server_name));
+
+        let mut this = Self {
+            dir,
+            file,
+            chunks: HashMap::new(),
+            markers: vec![],
+            center: (0.0, 0.0),
+            scale: DEFAULT_MAP_SCALE,
+            unsaved: false,
+            last_save: Instant::now(),
+        };
+
+        if let Some(saved) = this.dir.read_object::<SavedWorldMap>(&this.file) {
+            for chunk in saved.chunks {
+                if chunk.cells.len() != CHUNK_TILES {
+                    warn!("Skipping corrupted map chunk at {}, {}", chunk.x, chunk.y);
+                    continue;
+                }
+                this.chunks.insert(
+                    (chunk.x, chunk.y),
+                    MapChunk::from_cells(chunk.cells.into_boxed_slice()),
+                );
+            }
+            this.markers = saved.markers;
+            debug!("Loaded world map {}", this.file);
+        }
+
+        this
+    }
+
+    pub fn save(&mut self) {
+        self.unsaved = false;
+        self.last_save = Instant::now();
+        let saved = SavedWorldMap {
+            chunks: self
+                .chunks
+                .iter()
+                .map(|(pos, chunk)| SavedMapChunk {
+                    x: pos.0,
+                    y: pos.1,
+                    cells: chunk.cells.to_vec(),
+                })
+                .collect(),
+            markers: self.markers.clone(),
+        };
+        if let Some(_) = self.dir.save_object(&saved, &self.file) {
+            debug!("Saved world map {}", self.file);
+        }
+    }
+
+    /// Saves the map if something was explored and the last save is long enough ago.
+    pub fn save_if_due(&mut self) {
+        if self.unsaved && self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    pub fn record(&mut self, pos: ChunkPos, chunk: MapChunk) {
+        self.chunks.insert(pos, chunk);
+        self.unsaved = true;
+    }
+
+    /// Updates one cell of a recorded chunk, returns false if the chunk was never recorded.
+    pub fn set_cell(&mut self, pos: ChunkPos, cell_index: usize, cell: u16) -> bool {
+        match self.chunks.get_mut(&pos) {
+            Some(chunk) => {
+                self.unsaved |= chunk.set_cell(cell_index, cell);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn explored(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn add_marker(&mut self, name: String) {
+        self.markers.push(MapMarker {
+            name,
+            x: self.center.0.floor() as i32,
+            y: self.center.1.floor() as i32,
+        });
+    }
+
+    /// Removes the marker closest to the center of the map, if there is one close enough.
+    pub fn remove_marker_near_center(&mut self) -> Option<MapMarker> {
+        let (cx, cy) = self.center;
+        let (idx, dist) = self
+            .markers
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (i, (m.x as f64 + 0.5 - cx).hypot(m.y as f64 + 0.5 - cy)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        if dist > MARKER_PICK_RADIUS {
+            return None;
+        }
+        Some(self.markers.remove(idx))
+    }
+
+    pub fn focus(&mut self, pos: TileUnit) {
+        self.center = pos;
+    }
+
+    pub fn focus_marker(&mut self, index: usize) {
+        if let Some(marker) = self.markers.get(index) {
+            self.center = (marker.x as f64 + 0.5, marker.y as f64 + 0.5);
+        }
+    }
+
+    /// Moves the map by a distance in pixels, so panning feels the same at every zoom level.
+    pub fn pan(&mut self, by: (f64, f64)) {
+        self.center.0 += by.0 / self.scale;
+        self.center.1 += by.1 / self.scale;
+    }
+
+    pub fn zoom(&mut self, factor: f64) {
+        self.scale = (self.scale * factor).clamp(MIN_MAP_SCALE, MAX_MAP_SCALE);
+    }
+
+    pub fn draw(&self, ctx: &mut impl WideRenderContext, width: i32, height: i32, player: TileUnit) {
+        let z = ctx.next_z();
+        drawutils::rect(ctx, 0, 0, width, height, Fill::Color(RgbColor::black()), z);
+
+        let (cx, cy) = self.center;
+        let scale = self.scale;
+        let to_screen = |tx: f64, ty: f64| {
+            (
+                width / 2 + ((tx - cx) * scale).floor() as i32,
+                height / 2 + ((ty - cy) * scale).floor() as i32,
+            )
+        };
+
+        for (pos, chunk) in &self.chunks {
+            let (sx, sy) = to_screen(
+                (pos.0 * CHUNK_SIZE) as f64,
+                (pos.1 * CHUNK_SIZE) as f64,
+            );
+            let chunk_px = (CHUNK_SIZE as f64 * scale).ceil() as i32;
+            if sx + chunk_px < 0 || sy + chunk_px < 0 || sx > width || sy > height {
+                continue;
+            }
+            for run in &chunk.runs {
+                //compute both edges from tile coordinates so neighbouring runs never leave gaps
+                let x1 = sx + (run.x as f64 * scale).floor() as i32;
+                let x2 = sx + ((run.x + run.len) as f64 * scale).floor() as i32;
+                let y1 = sy + (run.y as f64 * scale).floor() as i32;
+                let y2 = sy + ((run.y + 1) as f64 * scale).floor() as i32;
+                if x2 <= x1 || y2 <= y1 {
+                    continue;
+                }
+                drawutils::rect(
+                    ctx,
+                    x1,
+                    y1,
+                    x2 - x1,
+                    y2 - y1,
+                    Fill::Color(run.color.clone()),
+                    z,
+                );
+            }
+        }
+
+        for marker in &self.markers {
+            let (sx, sy) = to_screen(marker.x as f64 + 0.5, marker.y as f64 + 0.5);
+            drawutils::rect(ctx, sx - 4, sy - 4, 9, 9, Fill::Color(RgbColor::black()), z);
+            drawutils::rect(ctx, sx - 3, sy - 3, 7, 7, Fill::Color(RgbColor::white()), z);
+        }
+
+        let (sx, sy) = to_screen(player.0, player.1);
+        drawutils::rect(ctx, sx - 3, sy - 3, 7, 7, Fill::Color(RgbColor::yellow()), z);
+
+        //crosshair in the middle, markers are placed here
+        drawutils::rect(ctx, width / 2 - 6, height / 2, 13, 1, Fill::Color(RgbColor::white()), z);
+        drawutils::rect(ctx, width / 2, height / 2 - 6, 1, 13, Fill::Color(RgbColor::white()), z);
+    }
+}
@@ -1,25 +1,40 @@
 use crate::drawutils::Fill;
+use crate::game::blueprint::BlueprintTool;
+use crate::game::destroy::{DestroyTarget, DestroyTool};
+use crate::game::drag_build::DragBuild;
+use crate::game::history::{BuildHistory, HistoryEntry, TileChange};
+use crate::game::hotbar::{Hotbar, SavedHotbar};
+use crate::game::inspector;
+use crate::game::minimap::Minimap;
+use crate::game::worldmap::WorldMap;
 use crate::game::{place_tile, Game};
 use crate::gameloop::FactoryIslandClient;
 use crate::gamesettings::GameSettings;
-use crate::input::{ESCAPE, ROTATE_L, ROTATE_R};
+use crate::input::{ESCAPE, ROTATE_L, ROTATE_R, ZOOM_IN, ZOOM_OUT};
 use crate::player::ClientPlayer;
 use crate::rendering::WorldShaders;
 use crate::res::R;
 use crate::ui::display::chat::Chat;
+use crate::ui::display::nametags::NameTags;
+use crate::ui::display::tooltip::Tooltip;
 use crate::ui::display::TileSelection;
-use crate::ui::manager::{GameUiManager, UI_ESCAPE_SCREEN};
+use crate::ui::manager::{GameUiManager, UI_ESCAPE_SCREEN, UI_MAP_SCREEN};
+use crate::ui::map_screen::MapScreen;
+use crate::world::map::MapChunk;
 use crate::world::tiles::impls::CLIENT_TILE_REG;
-use crate::world::ClientWorld;
+use crate::world::{tile_pos_at, ClientWorld};
 use crate::{drawutils, input};
 use api::ingredients::IngredientKind;
+use api::inventory::InventoryOwner;
 use api::server::packets::common::{ClientDataPacket, ServerStatePacket};
+use api::server::packets::inventory::InventoryOpenPacket;
 use api::server::packets::world::TileSetFromClientPacket;
 use api::server::{ClientBoundPacket, ServerBoundPacket};
 use api::world::tiles::pos::TilePos;
-use api::world::tiles::Orientation;
-use api::world::SingleTileUnit;
-use log::trace;
+use api::world::tiles::{Orientation, TileKind};
+use api::world::chunk::Chunk;
+use api::world::{ChunkPos, SingleTileUnit};
+use log::{debug, trace};
 use mvengine::input::consts::MouseButton;
 use mvengine::math::vec::Vec2;
 use mvengine::modify_style;
@@ -44,6 +59,17 @@ use crate::ui::display::inventory::{CurrentInvDisplay, InventoryDisplay};
 
 pub type RP = RenderingPipeline<OpenGLRenderer>;
 
+/// Pixels per second the world map moves while a movement key is held.
+const MAP_PAN_SPEED: f64 = 600.0;
+const MAP_ZOOM_STEP: f64 = 1.25;
+
+pub const DEFAULT_TILE_SIZE: i32 = 50;
+pub const MIN_TILE_SIZE: i32 = 16;
+pub const MAX_TILE_SIZE: i32 = 128;
+const ZOOM_STEP: f32 = 1.2;
+/// Seconds the cursor has to rest on a tile before the inspector describes it.
+const INSPECT_DELAY: f64 = 0.4;
+
 /// This hold everything only present when the player is inside a world.
 pub struct WorldView {
     //ui
@@ -51,7 +77,14 @@ pub struct WorldView {
     pub tile_selection: TileSelection,
     pub ingredients: Vec<IngredientKind>,
     pub chat: Chat,
+    name_tags: NameTags,
+    inspector: Tooltip,
+    pub placement_hint: Tooltip,
+    /// Tile under the cursor and for how many seconds it has been there.
+    hovered: Option<((i32, i32), f64)>,
     pub inventory: CurrentInvDisplay,
+    pub minimap: Minimap,
+    pub world_map: WorldMap,
 
     //game
     pub world: ClientWorld,
@@ -59,6 +92,12 @@ pub struct WorldView {
     pub player: ClientPlayer,
     pub other_players: HashMap<ClientId, ClientPlayer, U64IdentityHasher>,
     pub orientation: Orientation,
+    pub hotbar: Hotbar,
+    pub destroy: DestroyTool,
+    pending_interact: Option<TilePos>,
+    pub drag: DragBuild,
+    pub blueprints: BlueprintTool,
+    pub history: BuildHistory,
 
     //rendering
     pub world_pipeline: RP,
@@ -77,7 +116,9 @@ impl WorldView {
         };
 
         let mut map = HashMap::with_hasher(U64IdentityHasher::default());
+        let mut name_tags = NameTags::new(window);
         for entry in server_state_packet.players {
+            name_tags.add(entry.client_id, entry.data.profile.name.clone());
             let player = ClientPlayer::new(window.width(), window.height(), entry.data);
             map.insert(entry.client_id, player);
         }
@@ -109,20 +150,32 @@ impl WorldView {
             tile_selection: TileSelection::new(window, server_state_packet.tiles.into_iter()),
             ingredients: server_state_packet.ingredients,
             chat: Chat::new(window),
+            name_tags,
+            inspector: Tooltip::new(window),
+            placement_hint: Tooltip::new(window).with_offset(16, -48),
+            hovered: None,
             inventory: CurrentInvDisplay::new(window),
+            minimap: Minimap::new(),
+            world_map: WorldMap::load(game),
             world: ClientWorld::new(),
-            tile_size: 50,
+            tile_size: DEFAULT_TILE_SIZE,
             player: ClientPlayer::new(
                 1,
                 1,
                 ClientDataPacket {
                     profile: game.profile.clone(),
-                    render_distance: 1,
+                    render_distance: game.settings.render_distance(),
                     client_id: server_state_packet.client_id,
                 },
             ),
             other_players: map,
             orientation: Orientation::North,
+            hotbar: Hotbar::new(),
+            destroy: DestroyTool::new(),
+            pending_interact: None,
+            drag: DragBuild::new(),
+            blueprints: BlueprintTool::new(game.configuration_directory()),
+            history: BuildHistory::new(),
             world_pipeline,
             player_pipeline,
             overlay_pipeline,
@@ -130,6 +183,12 @@ impl WorldView {
             initialized: false,
         };
 
+        if let Some(saved) = game.persistent_game_data.hotbar_for(&game.server_name) {
+            this.hotbar = Hotbar::from_saved(saved);
+            this.tile_selection.select(saved.selected());
+            this.orientation = saved.orientation();
+        }
+
         this.resize(window);
 
         this
@@ -139,12 +198,18 @@ impl WorldView {
         self.tile_selection
             .open(window, self.click_area.as_ref().clone());
         window.ui_mut().add_root(self.click_area.as_ref().clone());
+        self.name_tags.open(window);
+        self.inspector.open(window);
+        self.placement_hint.open(window);
     }
 
     pub fn close(&mut self, window: &mut Window) {
         window
             .ui_mut()
             .remove_root(self.click_area.as_ref().clone());
+        self.name_tags.close(window);
+        self.inspector.close(window);
+        self.placement_hint.close(window);
     }
 
     pub fn resize(&mut self, window: &Window) {
@@ -160,10 +225,12 @@ impl WorldView {
     }
 
     pub fn player_join(&mut self, player: ClientPlayer, id: ClientId) {
+        self.name_tags.add(id, player.profile().name.clone());
         self.other_players.insert(id, player);
     }
 
     pub fn player_leave(&mut self, id: ClientId) {
+        self.name_tags.remove(id);
         self.other_players.remove(&id);
     }
 
@@ -204,9 +271,11 @@ impl WorldView {
         //draw clouds
         if *settings.cloud_shader.read() {
             let cam_pos = Vec2::from_i32s(self.player.camera.pos);
+            let zoom = self.tile_size as f32 / DEFAULT_TILE_SIZE as f32;
             self.world_pipeline.advance(window, |s| {
                 s.uniform_1f("FRAME", self.frame as f32);
                 s.uniform_2fv("CAM", &cam_pos);
+                s.uniform_1f("ZOOM", zoom);
             });
         } else {
             self.world_pipeline.skip();
@@ -218,9 +287,21 @@ impl WorldView {
         self.world_pipeline.next_pipeline(&mut self.player_pipeline);
 
         self.draw_players();
+        self.name_tags.update(
+            &self.player.camera.view_area,
+            self.tile_size,
+            &self.other_players,
+        );
+        self.minimap
+            .draw(&mut self.player_pipeline, &self.player, &self.other_players);
         self.player_pipeline.advance(window, |_| {});
 
-        if let Some(_) = self.tile_selection.selected_tile() {
+        if self.tile_selection.selected_tile().is_some()
+            || self.drag.is_dragging()
+            || self.blueprints.is_active()
+            || self.destroy.is_breaking()
+            || self.destroy.awaiting_confirmation()
+        {
             place_tile::draw_overlay(self, window, settings);
 
             if let Some(next) = next_pipeline {
@@ -229,6 +310,7 @@ impl WorldView {
                 self.overlay_pipeline.flush();
             }
         } else {
+            self.placement_hint.hide();
             if let Some(next) = next_pipeline {
                 self.player_pipeline.next_pipeline(next);
             } else {
@@ -237,6 +319,255 @@ impl WorldView {
         }
     }
 
+    /// Changes the tile size while keeping the player where it is on the screen.
+    /// Everything in world space (picking, culling, reach) derives from the tile size and camera, so this is all zooming needs.
+    pub fn set_tile_size(&mut self, tile_size: i32) {
+        let tile_size = tile_size.clamp(MIN_TILE_SIZE, MAX_TILE_SIZE);
+        if tile_size == self.tile_size {
+            return;
+        }
+        self.player.rescale(self.tile_size, tile_size);
+        self.tile_size = tile_size;
+    }
+
+    pub fn zoom(&mut self, factor: f32) {
+        let scaled = (self.tile_size as f32 * factor).round() as i32;
+        //make sure small tile sizes still move by at least one pixel
+        let tile_size = if factor > 1.0 {
+            scaled.max(self.tile_size + 1)
+        } else {
+            scaled.min(self.tile_size - 1)
+        };
+        self.set_tile_size(tile_size);
+    }
+
+    /// Sets every tile in reach that differs from what is already there, tile id 0 removes.
+    /// The server validates each of them on its own and sends back what actually happened.
+    fn set_tiles(
+        &mut self,
+        tiles: Vec<((i32, i32), TileKind, Orientation)>,
+        client: &mut FactoryIslandClient,
+    ) {
+        let changes = self.send_tiles(tiles, client);
+        self.history.record(changes);
+    }
+
+    /// Sends every tile in reach that differs from the world and returns what was replaced.
+    fn send_tiles(
+        &mut self,
+        tiles: Vec<((i32, i32), TileKind, Orientation)>,
+        client: &mut FactoryIslandClient,
+    ) -> HistoryEntry {
+        let mut changes = vec![];
+        for (raw, tile_id, orientation) in tiles {
+            let pos = tile_pos_at(raw);
+            if pos.distance_from(&self.player) > self.player.reach {
+                continue;
+            }
+            let before = match self.world.tile_at(raw) {
+                Some(tile) => (tile.id, tile.orientation),
+                None => (0, orientation),
+            };
+            let unchanged = before.0 == tile_id && (tile_id == 0 || before.1 == orientation);
+            if unchanged {
+                continue;
+            }
+            self.world.set_ghost_block(&pos, tile_id, orientation);
+            client.send(ServerBoundPacket::TileSet(TileSetFromClientPacket {
+                pos,
+                tile_id,
+                orientation,
+            }));
+            changes.push(TileChange {
+                raw,
+                before,
+                after: (tile_id, orientation),
+            });
+        }
+        changes
+    }
+
+    /// Replays the changes of an undo or redo, skipping tiles out of reach and tiles the world changed since.
+    fn replay(&mut self, entry: HistoryEntry, client: &mut FactoryIslandClient) -> HistoryEntry {
+        let total = entry.len();
+        let tiles: Vec<_> = entry
+            .into_iter()
+            .filter(|change| change.applies_to(&self.world))
+            .map(|change| (change.raw, change.after.0, change.after.1))
+            .collect();
+        let applied = self.send_tiles(tiles, client);
+        if applied.len() < total {
+            debug!(
+                "Skipped {} of {} tiles that changed since or are out of reach",
+                total - applied.len(),
+                total
+            );
+        }
+        applied
+    }
+
+    /// Selects the kind and orientation of the tile at `pos`, like a pipette.
+    fn pick(&mut self, pos: &TilePos) {
+        if let Some(tile) = self.world.tile_at(pos.raw) {
+            self.tile_selection.select(Some(tile.id));
+            self.orientation = tile.orientation;
+        }
+    }
+
+    pub fn saved_hotbar(&self, server: String) -> SavedHotbar {
+        self.hotbar.to_saved(
+            server,
+            self.tile_selection.selected_tile().copied(),
+            self.orientation,
+        )
+    }
+
+    fn destroy_target(&self, pos: &TilePos) -> Option<DestroyTarget> {
+        if pos.distance_from(&self.player) > self.player.reach {
+            return None;
+        }
+        if let Some(placement) = self.world.multitile_at(pos) {
+            Some(DestroyTarget::MultiTile(placement.uuid.clone()))
+        } else if self.world.tile_at(pos.raw).is_some() {
+            Some(DestroyTarget::Tile(pos.raw))
+        } else {
+            None
+        }
+    }
+
+    fn finish_destroy(
+        &mut self,
+        target: DestroyTarget,
+        cursor: &TilePos,
+        client: &mut FactoryIslandClient,
+        settings: &GameSettings,
+    ) {
+        match target {
+            DestroyTarget::Tile(raw) => {
+                self.set_tiles(vec![(raw, 0, self.orientation)], client);
+            }
+            DestroyTarget::MultiTile(uuid) => {
+                let target = DestroyTarget::MultiTile(uuid.clone());
+                if *settings.confirm_destroy.read() && !self.destroy.confirm(&target) {
+                    return;
+                }
+                let Some(placement) = self
+                    .world
+                    .multitile_at(cursor)
+                    .filter(|placement| placement.uuid == uuid)
+                else {
+                    return;
+                };
+                //clearing the origin of a multitile removes all of it
+                client.send(ServerBoundPacket::TileSet(TileSetFromClientPacket {
+                    pos: placement.pos.clone(),
+                    tile_id: 0,
+                    orientation: self.orientation,
+                }));
+            }
+        }
+    }
+
+    /// Asks the server to open the inventory of the tile or multitile at `pos`.
+    fn interact(&mut self, pos: &TilePos, client: &mut FactoryIslandClient) {
+        if pos.distance_from(&self.player) > self.player.reach {
+            return;
+        }
+        let owner = if let Some(placement) = self.world.multitile_at(pos) {
+            InventoryOwner::MultiTile(placement.uuid.clone())
+        } else if self.world.tile_at(pos.raw).is_some() {
+            InventoryOwner::Tile(pos.clone())
+        } else {
+            return;
+        };
+        client.send(ServerBoundPacket::InventoryOpenPacket(InventoryOpenPacket {
+            owner,
+        }));
+    }
+
+    fn undo(&mut self, client: &mut FactoryIslandClient) {
+        if let Some(entry) = self.history.take_undo() {
+            let applied = self.replay(entry, client);
+            self.history.undone(applied);
+        }
+    }
+
+    fn redo(&mut self, client: &mut FactoryIslandClient) {
+        if let Some(entry) = self.history.take_redo() {
+            let applied = self.replay(entry, client);
+            self.history.redone(applied);
+        }
+    }
+
+    pub fn draw_map(&self, ctx: &mut RP) {
+        self.world_map.draw(
+            ctx,
+            self.player.camera.width,
+            self.player.camera.height,
+            self.player.pos(),
+        );
+    }
+
+    fn update_map(&mut self, pos: ChunkPos) {
+        if let Some(chunk) = self.world.get_chunk(pos) {
+            let map_chunk = MapChunk::from_client_chunk(pos, chunk);
+            self.world_map.record(pos, map_chunk.clone());
+            self.minimap.insert(pos, map_chunk);
+        }
+    }
+
+    /// Updates the single map cell a tile set touched, state only updates leave the map as it is.
+    fn update_map_tile(&mut self, pos: &TilePos) {
+        let Some(chunk) = self.world.get_chunk(pos.chunk_pos) else {
+            return;
+        };
+        let index = Chunk::get_index(pos);
+        let cell = MapChunk::cell_of(chunk, index);
+        let cell_index = MapChunk::cell_index(pos.chunk_pos, index);
+        let recorded = self.world_map.set_cell(pos.chunk_pos, cell_index, cell);
+        let shown = self.minimap.set_cell(pos.chunk_pos, cell_index, cell);
+        if !recorded || !shown {
+            self.update_map(pos.chunk_pos);
+        }
+    }
+
+    /// Multitiles can reach into the neighbouring chunks, so those have to be rebuilt too.
+    fn update_map_around(&mut self, pos: ChunkPos) {
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                self.update_map((pos.0 + dx, pos.1 + dy));
+            }
+        }
+    }
+
+    /// Whether keys are typed into some text box, key bound actions must not react then.
+    fn is_typing(&self, ui_manager: &GameUiManager) -> bool {
+        let map_typing = ui_manager.is_open(UI_MAP_SCREEN)
+            && ui_manager.uis[UI_MAP_SCREEN]
+                .callbacks
+                .as_any()
+                .downcast_ref::<MapScreen>()
+                .is_some_and(MapScreen::is_typing);
+        self.chat.open || map_typing || self.tile_selection.is_typing()
+    }
+
+    fn toggle_map(&mut self, window: &mut Window, ui_manager: &mut GameUiManager) {
+        if ui_manager.is_open(UI_MAP_SCREEN) {
+            ui_manager.close_all(window);
+            return;
+        }
+        self.world_map.focus(self.player.pos());
+        ui_manager.goto(UI_MAP_SCREEN, window);
+        if let Some(screen) = ui_manager.uis[UI_MAP_SCREEN]
+            .callbacks
+            .as_any_mut()
+            .downcast_mut::<MapScreen>()
+        {
+            screen.reset_focus();
+            screen.refresh(window, &self.world_map);
+        }
+    }
+
     pub fn draw_players(&mut self) {
         for player in self.other_players.values() {
             player.draw_from_other_pov(
@@ -254,40 +585,102 @@ impl WorldView {
         window: &mut Window,
         client: &mut FactoryIslandClient,
         ui_manager: &mut GameUiManager,
+        settings: &GameSettings,
     ) {
         if !self.initialized {
             self.initialized = true;
 
             client.send(ServerBoundPacket::ClientData(self.player.data.clone()));
         }
+        //the server streams chunks based on our client data, so it has to know when the distance changes
+        let render_distance = settings.render_distance();
+        if render_distance != self.player.data.render_distance {
+            self.player.data.render_distance = render_distance;
+            client.send(ServerBoundPacket::ClientData(self.player.data.clone()));
+        }
+        let delay = settings.interpolation_delay();
+        for player in self.other_players.values_mut() {
+            player.interpolate(delay);
+        }
+
+        let map_open = ui_manager.is_open(UI_MAP_SCREEN);
         if window.input.was_action(ESCAPE) {
-            ui_manager.goto(UI_ESCAPE_SCREEN, window);
+            if map_open {
+                ui_manager.close_all(window);
+            } else if self.drag.is_dragging() {
+                self.drag.cancel();
+            } else if self.blueprints.is_active() {
+                self.blueprints.close();
+            } else if self.inventory.is_open() {
+                self.inventory.close();
+            } else {
+                ui_manager.goto(UI_ESCAPE_SCREEN, window);
+            }
+        }
+        let typing = self.is_typing(ui_manager);
+        if !typing && window.input.was_action(input::MAP) {
+            self.toggle_map(window, ui_manager);
+        }
+        if !typing && window.input.was_action(input::INVENTORY) {
+            if self.inventory.is_open() {
+                self.inventory.close();
+            } else {
+                client.send(ServerBoundPacket::InventoryOpenPacket(InventoryOpenPacket {
+                    owner: InventoryOwner::Player,
+                }));
+            }
+        }
+        self.world_map.save_if_due();
+        let mouse = (window.input.mouse_x, window.input.mouse_y);
+        let pos = TilePos::from_screen(mouse, &self.player.camera.view_area, self.tile_size);
+        self.hovered = match self.hovered {
+            Some((raw, rested)) if raw == pos.raw => Some((raw, rested + window.get_delta_t())),
+            _ => Some((pos.raw, 0.0)),
+        };
+        let resting = self.hovered.is_some_and(|(_, rested)| rested >= INSPECT_DELAY);
+        //the placement hint uses the same spot next to the cursor while a tile is selected
+        if *settings.show_inspector.read()
+            && resting
+            && self.tile_selection.selected_tile().is_none()
+            && !ui_manager.any_open()
+            && !self.chat.open
+        {
+            self.inspector.show(inspector::inspect(&self.world, &pos), mouse);
+        } else {
+            self.inspector.hide();
+        }
+        if map_open {
+            self.on_map_frame(window, typing);
+            return;
         }
-        let mut has_moved = false;
         let speed = self.player.speed * window.get_delta_t();
-        if !self.chat.open {
+        let mut by = (0.0, 0.0);
+        if !typing {
             if window.input.is_action(input::MOVE_FORWARD) {
-                self.player.move_by((0.0, speed), self.tile_size);
-                has_moved = true;
+                by.1 += speed;
             }
             if window.input.is_action(input::MOVE_BACK) {
-                self.player.move_by((0.0, -speed), self.tile_size);
-                has_moved = true;
+                by.1 -= speed;
             }
             if window.input.is_action(input::MOVE_LEFT) {
-                self.player.move_by((-speed, 0.0), self.tile_size);
-                has_moved = true;
+                by.0 -= speed;
             }
             if window.input.is_action(input::MOVE_RIGHT) {
-                self.player.move_by((speed, 0.0), self.tile_size);
-                has_moved = true;
+                by.0 += speed;
             }
         }
+        let by = self.world.resolve_movement(self.player.pos(), by);
+        let has_moved = by != (0.0, 0.0);
+        if has_moved {
+            self.player.predict_move(by, self.tile_size);
+        }
+        self.player.smooth_correction(window.get_delta_t());
         if window.input.was_action(input::CHAT) {
             self.chat.toggle(window, client);
         }
-        if window.input.was_action(input::RELOAD_CHUNKS) {
+        if !typing && window.input.was_action(input::RELOAD_CHUNKS) {
             self.world.drop_all();
+            self.minimap.clear();
             client.send(ServerBoundPacket::RequestReload);
         }
 
@@ -295,47 +688,154 @@ impl WorldView {
             self.player.broadcast_position(client);
         }
 
-        self.tile_selection.check_events();
+        self.tile_selection.check_events(window);
+        self.inventory.check_events(window, client);
+        self.world.check_predictions();
 
         self.frame = self.frame.wrapping_add(1);
 
-        //tile set
-        if let Some(event) = &self.click_area.get().state().events.click_event {
-            if event.button == MouseButton::Left && event.base.action == UiClickAction::Click {
-                let screen_pos = (window.input.mouse_x, window.input.mouse_y);
-                if let Some(tile) = self.tile_selection.selected_tile() {
-                    let pos = TilePos::from_screen(
-                        screen_pos,
-                        &self.player.camera.view_area,
-                        self.tile_size,
-                    );
-                    if pos.distance_from(&self.player) <= self.player.reach {
-                        self.world.set_ghost_block(&pos, *tile, self.orientation);
-                        client.send(ServerBoundPacket::TileSet(TileSetFromClientPacket {
-                            pos,
-                            tile_id: *tile,
-                            orientation: self.orientation,
-                        }));
+        //tile set, pressing starts a drag and releasing places everything it covers
+        let pressed = self
+            .click_area
+            .get()
+            .state()
+            .events
+            .click_event
+            .as_ref()
+            .filter(|event| event.button == MouseButton::Left)
+            .and_then(|event| {
+                if event.base.action == UiClickAction::Click {
+                    Some(true)
+                } else if event.base.action == UiClickAction::Release {
+                    Some(false)
+                } else {
+                    None
+                }
+            });
+        if let Some(pressed) = pressed {
+            let pos = TilePos::from_screen(
+                (window.input.mouse_x, window.input.mouse_y),
+                &self.player.camera.view_area,
+                self.tile_size,
+            );
+            if self.blueprints.is_active() {
+                if pressed {
+                    if let Some(tiles) = self.blueprints.press(pos.raw) {
+                        self.set_tiles(tiles, client);
                     }
                 } else {
-                    let pos = TilePos::from_screen(
-                        screen_pos,
-                        &self.player.camera.view_area,
-                        self.tile_size,
-                    );
-                    if pos.distance_from(&self.player) <= self.player.reach {
-                        self.world.set_ghost_block(&pos, 0, self.orientation);
-                        client.send(ServerBoundPacket::TileSet(TileSetFromClientPacket {
-                            pos,
-                            tile_id: 0,
-                            orientation: self.orientation,
-                        }));
+                    self.blueprints.release(pos.raw, &self.world);
+                }
+            } else if pressed {
+                //removal drags are started by the destroy action, so clicks without a tile do nothing
+                if self.tile_selection.selected_tile().is_some() {
+                    self.drag.start(pos.raw);
+                }
+            } else if !self.drag.is_removing() {
+                let tile_id = if self.drag.is_removing() {
+                    Some(0)
+                } else {
+                    self.tile_selection.selected_tile().copied()
+                };
+                let tiles = self.drag.finish(pos.raw, self.orientation);
+                if let Some(tile_id) = tile_id {
+                    let tiles = tiles
+                        .into_iter()
+                        .map(|(raw, orientation)| (raw, tile_id, orientation))
+                        .collect();
+                    self.set_tiles(tiles, client);
+                }
+            }
+        } else if self.drag.is_dragging()
+            && !self.drag.is_removing()
+            && !window.input.is_action(input::PLACE)
+        {
+            //the button went up over some ui, so the release never reached the world
+            self.drag.cancel();
+        }
+        //only interact when the click reached the world and not some ui on top of it
+        let on_world = self.click_area.get().state().events.click_event.is_some();
+        if on_world && window.input.was_action(input::PICK) {
+            let pos = TilePos::from_screen(
+                (window.input.mouse_x, window.input.mouse_y),
+                &self.player.camera.view_area,
+                self.tile_size,
+            );
+            self.pick(&pos);
+        }
+        if !typing {
+            for (slot, action) in input::HOTBAR.iter().enumerate() {
+                if !window.input.was_action(action) {
+                    continue;
+                }
+                if window.input.is_action(input::HOTBAR_ASSIGN) {
+                    self.hotbar
+                        .assign(slot, self.tile_selection.selected_tile().copied());
+                } else if let Some(kind) = self.hotbar.get(slot) {
+                    self.tile_selection.select(Some(kind));
+                }
+            }
+        }
+        let cursor = TilePos::from_screen(
+            (window.input.mouse_x, window.input.mouse_y),
+            &self.player.camera.view_area,
+            self.tile_size,
+        );
+        let hovered = self.destroy_target(&cursor);
+        self.destroy.update(hovered.as_ref(), window.get_delta_t());
+        if on_world && window.input.was_action(input::DESTROY) {
+            self.destroy.start(cursor.raw);
+        }
+        if self.destroy.is_active() {
+            if !window.input.is_action(input::DESTROY) {
+                //released over ui the area is still removed, unlike a placing drag
+                if self.destroy.stop() && self.drag.is_removing() {
+                    let tiles = self
+                        .drag
+                        .finish(cursor.raw, self.orientation)
+                        .into_iter()
+                        .map(|(raw, orientation)| (raw, 0, orientation))
+                        .collect();
+                    self.set_tiles(tiles, client);
+                }
+            } else if self.destroy.track(cursor.raw) {
+                //moving off the first tile turns the press into a removal drag of the current shape
+                if !self.drag.is_dragging() {
+                    if let Some(origin) = self.destroy.origin() {
+                        self.drag.start_removal(origin);
                     }
                 }
+            } else if let Some(broken) = self.destroy.hold(hovered, window.get_delta_t()) {
+                self.finish_destroy(broken, &cursor, client, settings);
             }
         }
+        //interaction happens on release, a press that turned into breaking is no interaction
+        if on_world && window.input.was_action(input::INTERACT) {
+            self.pending_interact = Some(cursor.clone());
+        }
+        if self.destroy.is_breaking() {
+            self.pending_interact = None;
+        } else if !window.input.is_action(input::INTERACT) {
+            if let Some(pos) = self.pending_interact.take() {
+                self.interact(&pos, client);
+            }
+        }
+        if !typing {
+            self.on_build_keys(window, client);
+        }
 
-        if window.input.was_action(ROTATE_L) {
+        //zooming is bound to ctrl + scroll, so it has to win over rotating
+        if window.input.was_action(ZOOM_IN) {
+            self.zoom(ZOOM_STEP);
+        } else if window.input.was_action(ZOOM_OUT) {
+            self.zoom(1.0 / ZOOM_STEP);
+        } else if self.blueprints.is_pasting() {
+            if window.input.was_action(ROTATE_L) {
+                self.blueprints.rotate(false);
+            } else if window.input.was_action(ROTATE_R) {
+                self.blueprints.rotate(true);
+            }
+        } else if window.input.was_action(ROTATE_L) {
             self.orientation = match self.orientation {
                 Orientation::North => Orientation::West,
                 Orientation::South => Orientation::East,
@@ -352,6 +852,60 @@ impl WorldView {
         }
     }
 
+    /// Key bound build tools, these must not react to keys typed into a text box.
+    fn on_build_keys(&mut self, window: &mut Window, client: &mut FactoryIslandClient) {
+        if window.input.was_action(input::UNDO) {
+            self.undo(client);
+        } else if window.input.was_action(input::REDO) {
+            self.redo(client);
+        }
+        if window.input.was_action(input::BUILD_SHAPE) {
+            self.drag.shape = self.drag.shape.next();
+        }
+        if window.input.was_action(input::BLUEPRINT) {
+            self.blueprints.toggle();
+            self.drag.cancel();
+        }
+        if self.blueprints.is_active() {
+            if window.input.was_action(input::BLUEPRINT_SAVE) {
+                self.blueprints.save_held();
+            }
+            if window.input.was_action(input::BLUEPRINT_LOAD) {
+                self.blueprints.cycle_library();
+            }
+        }
+    }
+
+    /// While the map is open the movement keys pan it and scrolling zooms it.
+    fn on_map_frame(&mut self, window: &mut Window, typing: bool) {
+        let speed = MAP_PAN_SPEED * window.get_delta_t();
+        let mut by = (0.0, 0.0);
+        if !typing {
+            if window.input.is_action(input::MOVE_FORWARD) {
+                by.1 += speed;
+            }
+            if window.input.is_action(input::MOVE_BACK) {
+                by.1 -= speed;
+            }
+            if window.input.is_action(input::MOVE_LEFT) {
+                by.0 -= speed;
+            }
+            if window.input.is_action(input::MOVE_RIGHT) {
+                by.0 += speed;
+            }
+        }
+        self.world_map.pan(by);
+
+        if window.input.was_action(ROTATE_L) {
+            self.world_map.zoom(MAP_ZOOM_STEP);
+        } else if window.input.was_action(ROTATE_R) {
+            self.world_map.zoom(1.0 / MAP_ZOOM_STEP);
+        }
+
+        self.world.check_predictions();
+        self.frame = self.frame.wrapping_add(1);
+    }
+
     pub fn check_window_packet(&mut self, packet: ClientBoundPacket, window: &mut Window, game: &Game) {
         match packet {
             ClientBoundPacket::InventoryDataPacket(packet) => {
@@ -366,17 +920,21 @@ impl WorldView {
     pub fn check_packet(&mut self, packet: ClientBoundPacket, game: &Game) {
         match packet {
             ClientBoundPacket::TileSet(packet) => {
+                let pos = packet.pos.clone();
                 self.world.sync(packet);
+                self.update_map_tile(&pos);
             }
             ClientBoundPacket::ChunkData(packet) => {
+                let pos = packet.pos;
                 self.world.sync_chunk(packet);
+                self.update_map_around(pos);
             }
             ClientBoundPacket::PlayerMove(packet) => {
-                self.player.move_to(packet.pos, self.tile_size);
+                self.player.correct(packet.pos, self.tile_size);
             }
             ClientBoundPacket::OtherPlayerMove(packet) => {
                 if let Some(player) = self.other_players.get_mut(&packet.client_id) {
-                    player.move_to(packet.pos, self.tile_size);
+                    player.push_snapshot(packet.pos);
                 }
             }
             ClientBoundPacket::OtherPlayerJoin(packet) => {
@@ -392,7 +950,10 @@ impl WorldView {
                 self.player_leave(packet.client_id);
             }
             ClientBoundPacket::ChunkUnload(packet) => {
+                //the world map keeps what was explored, only the minimap forgets it
                 self.world.drop_chunk(packet.pos);
+                self.minimap.remove_chunk(packet.pos);
+                self.update_map_around(packet.pos);
             }
             ClientBoundPacket::OtherPlayerChat(packet) => {
                 self.chat.push_message(packet);
@@ -401,23 +962,13 @@ impl WorldView {
                 self.player.data_packet(packet, self.tile_size);
             }
             ClientBoundPacket::MultiTilePlacedPacket(packet) => {
-                if let Some(chunk) = self.world.get_chunk_mut(packet.placement.pos.chunk_pos) {
-                    chunk.multitiles.push(packet.placement.into());
-                }
+                let pos = packet.placement.pos.chunk_pos;
+                self.world.add_multitile(packet.placement.into());
+                self.update_map_around(pos);
             }
             ClientBoundPacket::MultiTileDestroyedPacket(packet) => {
-                if let Some(chunk) = self.world.get_chunk_mut(packet.chunk_pos) {
-                    let mut remove = None;
-                    for i in 0..chunk.multitiles.len() {
-                        if chunk.multitiles[i].uuid == packet.placement_id {
-                            remove = Some(i);
-                            break;
-                        }
-                    }
-                    if let Some(i) = remove {
-                        chunk.multitiles.remove(i);
-                    }
-                }
+                self.world.remove_multitile(packet.chunk_pos, packet.placement_id);
+                self.update_map_around(packet.chunk_pos);
             }
             _ => {}
         }
@@ -60,12 +60,16 @@ pub struct GameHandler {
 }
 
 impl GameHandler {
-    pub fn new(is_internal: bool, sync: Option<ServerSync>) -> Arc<RwLock<Self>> {
+    pub fn new(
+        is_internal: bool,
+        world: Option<String>,
+        sync: Option<ServerSync>,
+    ) -> Arc<RwLock<Self>> {
         let this = Self {
             this: CreateOnce::new(),
             client: None,
             ui_pipeline: CreateOnce::new(),
-            game: Game::new(is_internal),
+            game: Game::new(is_internal, world),
             ui_manager: CreateOnce::new(),
             server_packet: None,
             window_packet_queue: vec![],
@@ -87,6 +91,7 @@ impl WindowCallbacks for GameHandler {
     fn post_init(&mut self, window: &mut Window) {
         unsafe {
             R::initialize();
+            world::terrain::register_terrain();
             world::tiles::impls::register_tiles();
             world::multitiles::register_all();
             ingredients::register_ingredients();
@@ -145,6 +150,12 @@ impl WindowCallbacks for GameHandler {
         let unsafe_self = unsafe { Unsafe::cast_lifetime_mut(self) };
         self.ui_manager.check_events(window, unsafe_self);
 
+        if self.ui_manager.is_open(manager::UI_MAP_SCREEN) {
+            if let Some(view) = &self.game.world_view {
+                view.draw_map(&mut *self.ui_pipeline);
+            }
+        }
+
         let a = window.area();
         window.ui_mut().draw(&mut *self.ui_pipeline, &a);
         //OpenGLRenderer::disable_depth_test();
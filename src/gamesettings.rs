@@ -1,14 +1,27 @@
-use mvutils::save::Savable;
+use mvutils::save::{Loader, Savable, Saver};
 use mvutils::state::State;
-use mvutils::Savable;
+use std::time::Duration;
 
 pub(crate) const SETTINGS_FILE: &str = "settings.sav";
 
-#[derive(Clone, Savable)]
+pub const MIN_RENDER_DISTANCE: i32 = 1;
+pub const MAX_RENDER_DISTANCE: i32 = 10;
+const DEFAULT_RENDER_DISTANCE: i32 = 2;
+const DEFAULT_INTERPOLATION_DELAY: f32 = 100.0;
+
+#[derive(Clone)]
 pub struct GameSettings {
     pub ssao_shader: State<bool>,
     pub cloud_shader: State<bool>,
     pub indicator_circle: State<bool>,
+    /// Chunk radius the server streams to us, bound to the slider in the settings.
+    pub render_distance: State<f32>,
+    /// Milliseconds other players are shown in the past, so their movement can be interpolated.
+    pub interpolation_delay: State<f32>,
+    /// Breaking a machine needs a second hold to go through.
+    pub confirm_destroy: State<bool>,
+    /// Describes the tile the cursor rests on in a tooltip.
+    pub show_inspector: State<bool>,
 }
 
 impl GameSettings {
@@ -17,6 +30,46 @@ impl GameSettings {
             ssao_shader: State::new(true),
             cloud_shader: State::new(true),
             indicator_circle: State::new(true),
+            render_distance: State::new(DEFAULT_RENDER_DISTANCE as f32),
+            interpolation_delay: State::new(DEFAULT_INTERPOLATION_DELAY),
+            confirm_destroy: State::new(false),
+            show_inspector: State::new(true),
         }
     }
+
+    pub fn render_distance(&self) -> i32 {
+        let distance = self.render_distance.read().round() as i32;
+        distance.clamp(MIN_RENDER_DISTANCE, MAX_RENDER_DISTANCE)
+    }
+
+    pub fn interpolation_delay(&self) -> Duration {
+        Duration::from_millis(self.interpolation_delay.read().max(0.0) as u64)
+    }
+}
+
+/// Fields are appended over time, so settings written by an older version simply end early.
+/// Every field after the original three falls back to its default instead of failing the whole file.
+impl Savable for GameSettings {
+    fn save(&self, saver: &mut impl Saver) {
+        self.ssao_shader.save(saver);
+        self.cloud_shader.save(saver);
+        self.indicator_circle.save(saver);
+        self.render_distance.save(saver);
+        self.interpolation_delay.save(saver);
+        self.confirm_destroy.save(saver);
+        self.show_inspector.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+        let defaults = Self::new();
+        Ok(Self {
+            ssao_shader: State::load(loader)?,
+            cloud_shader: State::load(loader)?,
+            indicator_circle: State::load(loader)?,
+            render_distance: State::load(loader).unwrap_or(defaults.render_distance),
+            interpolation_delay: State::load(loader).unwrap_or(defaults.interpolation_delay),
+            confirm_destroy: State::load(loader).unwrap_or(defaults.confirm_destroy),
+            show_inspector: State::load(loader).unwrap_or(defaults.show_inspector),
+        })
+    }
 }
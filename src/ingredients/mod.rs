@@ -1,10 +1,12 @@
 use crate::drawutils;
 use crate::drawutils::Fill;
+use crate::lang;
 use crate::res::R;
+use api::ingredients::IngredientStack;
 use api::registry::{Registerable, Registry};
 use api::world::chunk::ToClientObject;
 use api::world::tiles::Orientation;
-use api::world::{PixelUnit, TileUnit};
+use api::world::{resolve_unit, PixelUnit, TileUnit};
 use mvengine::color::RgbColor;
 use mvengine::graphics::Drawable;
 use mvengine::ui::geometry::SimpleRect;
@@ -21,21 +23,60 @@ lazy! {
 pub struct ClientIngredient {
     pub id: usize,
     pub texture: Drawable,
-    pub override_bg: Option<RgbColor>
+    pub override_bg: Option<RgbColor>,
+    /// Registry name, empty if the ingredient was registered without one.
+    pub name: &'static str,
+    /// Language key of the name shown to players, empty to fall back to the registry name.
+    pub display_name: &'static str,
+    /// Language key of the description, empty if there is none.
+    pub description: &'static str,
+}
+
+impl ClientIngredient {
+    /// Name for the ui, falls back to the registry name and then the id.
+    pub fn label(&self) -> String {
+        if !self.display_name.is_empty() {
+            lang::text(self.display_name).to_string()
+        } else if !self.name.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("Ingredient #{}", self.id)
+        }
+    }
+
+    /// Description for the ui, None if the ingredient has none.
+    pub fn description(&self) -> Option<&'static str> {
+        (!self.description.is_empty()).then(|| lang::text(self.description))
+    }
 }
 
 pub struct ClientIngredientCreateInfo {
     pub texture: Drawable,
-    pub override_bg: Option<RgbColor>
+    pub override_bg: Option<RgbColor>,
+    pub name: &'static str,
+    pub display_name: &'static str,
+    pub description: &'static str,
 }
 
 impl ClientIngredientCreateInfo {
     pub fn new(texture: Drawable) -> Self {
-        Self { texture, override_bg: None }
+        Self { texture, override_bg: None, name: "", display_name: "", description: "" }
     }
 
     pub fn with_custom_background(texture: Drawable, override_bg: RgbColor) -> Self {
-        Self { texture, override_bg: Some(override_bg) }
+        Self { texture, override_bg: Some(override_bg), name: "", display_name: "", description: "" }
+    }
+
+    pub fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Language keys of the name and description players see in inventory tooltips.
+    pub fn display(mut self, display_name: &'static str, description: &'static str) -> Self {
+        self.display_name = display_name;
+        self.description = description;
+        self
     }
 }
 
@@ -43,12 +84,23 @@ impl Registerable for ClientIngredient {
     type CreateInfo = ClientIngredientCreateInfo;
 
     fn with_id(id: usize, info: Self::CreateInfo) -> Self {
-        Self { id, texture: info.texture, override_bg: info.override_bg }
+        Self {
+            id,
+            texture: info.texture,
+            override_bg: info.override_bg,
+            name: info.name,
+            display_name: info.display_name,
+            description: info.description,
+        }
     }
 }
 
 pub fn register_ingredients() {
-    CLIENT_INGREDIENT_REG.register(ClientIngredientCreateInfo::new(Drawable::Texture(R.texture.ingredient_stone)));
+    CLIENT_INGREDIENT_REG.register(
+        ClientIngredientCreateInfo::new(Drawable::Texture(R.texture.ingredient_stone))
+            .named("stone")
+            .display("ingredient.stone", "ingredient.stone.description"),
+    );
 }
 
 pub struct LoadedClientIngredient {
@@ -65,22 +117,31 @@ impl LoadedClientIngredient {
         })
     }
 
+    pub fn from_stack(stack: &IngredientStack) -> Option<Self> {
+        let template = CLIENT_INGREDIENT_REG.reference_object(stack.ingredient)?;
+        Some(LoadedClientIngredient {
+            id: template.id,
+            texture: template.texture.clone(),
+        })
+    }
+
     pub fn draw(
         &self,
-        ctx: &mut impl WideRenderContext,
+        ctx: &mut (impl WideRenderContext + ?Sized),
         view_area: &SimpleRect,
         at: TileUnit,
         tile_size: i32,
         y: f32,
     ) {
-        drawutils::draw_in_world_size(
+        let pos_px = resolve_unit(at, tile_size);
+        let rect = SimpleRect::new(pos_px.0, pos_px.1, IG_SIZE.0, IG_SIZE.1);
+        drawutils::drawable_rect(
             ctx,
             view_area,
-            at,
-            IG_SIZE,
-            Fill::Drawable(self.texture.clone(), Orientation::North),
-            tile_size,
-            y,
+            &rect,
+            &self.texture,
+            Orientation::North,
+            y as i32,
         );
     }
 }
@@ -1,3 +1,4 @@
+use crate::game::hotbar::HOTBAR_SLOTS;
 use crate::game::Game;
 use log::{error, info};
 use mvengine::input::consts::{Key, MouseButton};
@@ -16,6 +17,36 @@ pub const CHAT: &str = "chat";
 pub const RELOAD_CHUNKS: &str = "reload_chunks";
 pub const ROTATE_L: &str = "rotatel";
 pub const ROTATE_R: &str = "rotater";
+pub const MAP: &str = "map";
+pub const ZOOM_IN: &str = "zoom_in";
+pub const ZOOM_OUT: &str = "zoom_out";
+pub const BUILD_SHAPE: &str = "build_shape";
+pub const BLUEPRINT: &str = "blueprint";
+pub const BLUEPRINT_SAVE: &str = "blueprint_save";
+pub const BLUEPRINT_LOAD: &str = "blueprint_load";
+pub const UNDO: &str = "undo";
+pub const REDO: &str = "redo";
+/// Shares the right mouse button with [`DESTROY`] by default. A click interacts on release,
+/// unless holding it turned into breaking, which always wins. Rebinding either of them
+/// in the saved actions file separates the two.
+pub const INTERACT: &str = "interact";
+/// Opens the inventory of the player, or closes whatever inventory is open.
+pub const INVENTORY: &str = "inventory";
+pub const PICK: &str = "pick";
+/// Held to break the target under the cursor, moving off the first tile drags out an area to remove.
+/// See [`INTERACT`] for how the two share their default button.
+pub const DESTROY: &str = "destroy";
+/// Held while dragging out tiles, releasing it anywhere but over the world cancels the drag.
+/// Pressing it anywhere also takes the focus away from text boxes.
+pub const PLACE: &str = "place";
+/// Held while clicking an inventory slot to move the stack into the other inventory.
+pub const QUICK_MOVE: &str = "quick_move";
+/// Held while pressing a hotbar key to put the selected tile into that slot.
+pub const HOTBAR_ASSIGN: &str = "hotbar_assign";
+pub const HOTBAR: [&str; HOTBAR_SLOTS] = [
+    "hotbar_1", "hotbar_2", "hotbar_3", "hotbar_4", "hotbar_5", "hotbar_6", "hotbar_7",
+    "hotbar_8", "hotbar_9",
+];
 
 pub const PATH: &str = ".factoryisland/";
 
@@ -33,6 +64,25 @@ impl InputManager {
         actions.create_action(RELOAD_CHUNKS);
         actions.create_action(ROTATE_L);
         actions.create_action(ROTATE_R);
+        actions.create_action(MAP);
+        actions.create_action(ZOOM_IN);
+        actions.create_action(ZOOM_OUT);
+        actions.create_action(BUILD_SHAPE);
+        actions.create_action(BLUEPRINT);
+        actions.create_action(BLUEPRINT_SAVE);
+        actions.create_action(BLUEPRINT_LOAD);
+        actions.create_action(UNDO);
+        actions.create_action(REDO);
+        actions.create_action(INTERACT);
+        actions.create_action(INVENTORY);
+        actions.create_action(PICK);
+        actions.create_action(DESTROY);
+        actions.create_action(PLACE);
+        actions.create_action(QUICK_MOVE);
+        actions.create_action(HOTBAR_ASSIGN);
+        for action in HOTBAR {
+            actions.create_action(action);
+        }
 
         //defaults, get overridden by file
         actions.bind_action(MOVE_FORWARD, vec![RawInput::KeyPress(Key::W)]);
@@ -47,6 +97,49 @@ impl InputManager {
         );
         actions.bind_action(ROTATE_L, vec![RawInput::Scroll(Direction::Up)]);
         actions.bind_action(ROTATE_R, vec![RawInput::Scroll(Direction::Down)]);
+        actions.bind_action(MAP, vec![RawInput::KeyPress(Key::M)]);
+        actions.bind_action(BUILD_SHAPE, vec![RawInput::KeyPress(Key::Tab)]);
+        actions.bind_action(BLUEPRINT, vec![RawInput::KeyPress(Key::B)]);
+        actions.bind_action(BLUEPRINT_SAVE, vec![RawInput::KeyPress(Key::F5)]);
+        actions.bind_action(BLUEPRINT_LOAD, vec![RawInput::KeyPress(Key::F6)]);
+        actions.bind_action(INTERACT, vec![RawInput::Click(MouseButton::Right)]);
+        actions.bind_action(INVENTORY, vec![RawInput::KeyPress(Key::E)]);
+        actions.bind_action(PICK, vec![RawInput::Click(MouseButton::Middle)]);
+        //shares the button with interacting, see INTERACT
+        actions.bind_action(DESTROY, vec![RawInput::Click(MouseButton::Right)]);
+        actions.bind_action(PLACE, vec![RawInput::Click(MouseButton::Left)]);
+        actions.bind_action(HOTBAR_ASSIGN, vec![RawInput::KeyPress(Key::LControl)]);
+        actions.bind_action(QUICK_MOVE, vec![RawInput::KeyPress(Key::LShift)]);
+        let number_keys = [
+            Key::Key1,
+            Key::Key2,
+            Key::Key3,
+            Key::Key4,
+            Key::Key5,
+            Key::Key6,
+            Key::Key7,
+            Key::Key8,
+            Key::Key9,
+        ];
+        for (action, key) in HOTBAR.into_iter().zip(number_keys) {
+            actions.bind_action(action, vec![RawInput::KeyPress(key)]);
+        }
+        actions.bind_action(
+            UNDO,
+            vec![RawInput::KeyPress(Key::LControl), RawInput::KeyPress(Key::Z)],
+        );
+        actions.bind_action(
+            REDO,
+            vec![RawInput::KeyPress(Key::LControl), RawInput::KeyPress(Key::Y)],
+        );
+        actions.bind_action(
+            ZOOM_IN,
+            vec![RawInput::KeyPress(Key::LControl), RawInput::Scroll(Direction::Up)],
+        );
+        actions.bind_action(
+            ZOOM_OUT,
+            vec![RawInput::KeyPress(Key::LControl), RawInput::Scroll(Direction::Down)],
+        );
 
         let dir = game.configuration_directory();
         if let Ok(_) = input.load_actions(dir.path()) {
@@ -0,0 +1,43 @@
+use mvutils::lazy;
+use std::collections::HashMap;
+
+/// Texts players see, one `key=text` entry per line, lines starting with `#` are comments.
+const EN_US: &str = include_str!("../res/lang/en_us.lang");
+
+lazy! {
+    static TEXTS: HashMap<&'static str, &'static str> = parse(EN_US);
+}
+
+/// Text registered for `key`, the key itself if there is none so missing entries stand out.
+pub fn text(key: &str) -> &str {
+    TEXTS.get(key).copied().unwrap_or(key)
+}
+
+fn parse(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, text)| (key.trim(), text.trim()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entries_and_skips_comments() {
+        let texts = parse("# comment\n\nitem.a = Apple\nitem.b=Two = words\nbroken line\n");
+        assert_eq!(texts.len(), 2);
+        assert_eq!(texts["item.a"], "Apple");
+        assert_eq!(texts["item.b"], "Two = words");
+    }
+
+    #[test]
+    fn every_registered_text_is_not_empty() {
+        assert!(TEXTS.values().all(|text| !text.is_empty()));
+        assert_eq!(text("no.such.key"), "no.such.key");
+    }
+}
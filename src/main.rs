@@ -10,6 +10,7 @@ mod gameloop;
 mod gamesettings;
 mod ingredients;
 mod input;
+mod lang;
 mod player;
 mod rendering;
 mod res;
@@ -64,6 +65,7 @@ fn main() {
     }
 
     let mut sync = None;
+    let world_name = world.clone();
     if server {
         let sync1 = ServerSync::new();
         let cloned = sync1.clone();
@@ -75,7 +77,7 @@ fn main() {
     }
 
     mvlogger::init(stdout(), LevelFilter::Debug);
-    let handler = GameHandler::new(server, sync);
+    let handler = GameHandler::new(server, world_name, sync);
     let mut info = WindowCreateInfo::default();
     info.vsync = false;
     info.fps = 60;
@@ -0,0 +1,155 @@
+use api::world::TileUnit;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a player keeps moving in its last direction when no new positions arrive.
+const MAX_EXTRAPOLATION: Duration = Duration::from_millis(250);
+/// Time it takes to glide back onto the last known position once extrapolation ran out.
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+const MAX_SNAPSHOTS: usize = 32;
+
+struct Snapshot {
+    at: Instant,
+    pos: TileUnit,
+}
+
+/// Timestamped positions of a remote player. These are rendered a bit in the past,
+/// so there is almost always a newer position to interpolate towards.
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl SnapshotBuffer {
+    pub fn new() -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, pos: TileUnit) {
+        self.push_at(Instant::now(), pos);
+    }
+
+    fn push_at(&mut self, at: Instant, pos: TileUnit) {
+        if self.snapshots.len() >= MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(Snapshot { at, pos });
+    }
+
+    /// Position at `delay` before now, `None` if there never was a position.
+    pub fn sample(&mut self, delay: Duration) -> Option<TileUnit> {
+        self.sample_at(Instant::now(), delay)
+    }
+
+    fn sample_at(&mut self, now: Instant, delay: Duration) -> Option<TileUnit> {
+        let target = now.checked_sub(delay).unwrap_or(now);
+
+        //keep one snapshot older than the target to interpolate from
+        while self.snapshots.len() > 2 && self.snapshots[1].at <= target {
+            self.snapshots.pop_front();
+        }
+
+        let first = self.snapshots.front()?;
+        if target <= first.at || self.snapshots.len() == 1 {
+            return Some(first.pos);
+        }
+
+        if let Some(i) = self.snapshots.iter().position(|s| s.at >= target) {
+            let a = &self.snapshots[i - 1];
+            let b = &self.snapshots[i];
+            let t = fraction(target - a.at, b.at - a.at);
+            return Some(lerp(a.pos, b.pos, t));
+        }
+
+        //we ran out of positions, keep going in the last direction for a bit
+        let b = &self.snapshots[self.snapshots.len() - 1];
+        let a = &self.snapshots[self.snapshots.len() - 2];
+        let behind = target - b.at;
+        //never overshoot by more than the last step, bursts of packets would make the velocity explode
+        let overshoot = fraction(behind.min(MAX_EXTRAPOLATION), b.at - a.at).min(1.0);
+        //the player most likely stopped, so ease back to where it was last seen
+        let settle = fraction(behind.saturating_sub(MAX_EXTRAPOLATION), SETTLE_TIME).min(1.0);
+        let t = 1.0 + overshoot * (1.0 - settle);
+        Some(lerp(a.pos, b.pos, t))
+    }
+}
+
+fn fraction(part: Duration, whole: Duration) -> f64 {
+    if whole.is_zero() {
+        return 1.0;
+    }
+    part.as_secs_f64() / whole.as_secs_f64()
+}
+
+fn lerp(a: TileUnit, b: TileUnit, t: f64) -> TileUnit {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn assert_near(actual: Option<TileUnit>, expected: TileUnit) {
+        let actual = actual.expect("no position");
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-6 && (actual.1 - expected.1).abs() < 1e-6,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    /// Two positions one tile apart, 100ms after each other.
+    fn moving() -> (SnapshotBuffer, Instant) {
+        let start = Instant::now();
+        let mut buffer = SnapshotBuffer::new();
+        buffer.push_at(start, (0.0, 0.0));
+        buffer.push_at(start + ms(100), (1.0, 0.0));
+        (buffer, start)
+    }
+
+    #[test]
+    fn empty_buffer_has_no_position() {
+        assert!(SnapshotBuffer::new().sample(ms(100)).is_none());
+    }
+
+    #[test]
+    fn single_snapshot_is_returned_as_is() {
+        let start = Instant::now();
+        let mut buffer = SnapshotBuffer::new();
+        buffer.push_at(start, (2.0, 3.0));
+        assert_near(buffer.sample_at(start + ms(500), ms(100)), (2.0, 3.0));
+    }
+
+    #[test]
+    fn positions_between_snapshots_are_interpolated() {
+        let (mut buffer, start) = moving();
+        assert_near(buffer.sample_at(start + ms(150), ms(100)), (0.5, 0.0));
+        assert_near(buffer.sample_at(start + ms(175), ms(100)), (0.75, 0.0));
+    }
+
+    #[test]
+    fn targets_before_the_first_snapshot_stay_there() {
+        let (mut buffer, start) = moving();
+        assert_near(buffer.sample_at(start + ms(50), ms(100)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn extrapolation_overshoots_by_at_most_one_step() {
+        let (mut buffer, start) = moving();
+        assert_near(buffer.sample_at(start + ms(150), Duration::ZERO), (1.5, 0.0));
+        assert_near(buffer.sample_at(start + ms(300), Duration::ZERO), (2.0, 0.0));
+    }
+
+    #[test]
+    fn extrapolation_settles_back_on_the_last_snapshot() {
+        let (mut buffer, start) = moving();
+        let limit = start + ms(100) + MAX_EXTRAPOLATION;
+        assert_near(buffer.sample_at(limit + SETTLE_TIME / 2, Duration::ZERO), (1.5, 0.0));
+        assert_near(buffer.sample_at(limit + SETTLE_TIME, Duration::ZERO), (1.0, 0.0));
+        assert_near(buffer.sample_at(limit + ms(5000), Duration::ZERO), (1.0, 0.0));
+    }
+}
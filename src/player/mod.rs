@@ -1,7 +1,12 @@
+pub mod interpolation;
+pub mod prediction;
+
 use crate::camera::Camera;
 use crate::drawutils;
 use crate::drawutils::Fill;
 use crate::gameloop::FactoryIslandClient;
+use crate::player::interpolation::SnapshotBuffer;
+use crate::player::prediction::MovementPrediction;
 use crate::res::R;
 use api::player::profile::PlayerProfile;
 use api::server::packets::common::ClientDataPacket;
@@ -11,20 +16,41 @@ use api::world::tiles::pos::{TileDistance, TilePos};
 use api::world::tiles::Orientation;
 use api::world::{resolve_unit, SingleTileUnit, TileUnit};
 use log::debug;
+use mvengine::color::parse::parse_color;
+use mvengine::color::RgbColor;
 use mvengine::graphics::Drawable;
 use mvengine::math::vec::Vec2;
 use mvengine::rendering::RenderContext;
 use mvengine::ui::geometry::{geom, SimpleRect};
 use mvengine::ui::rendering::WideRenderContext;
+use mvutils::lazy;
+use std::time::Duration;
 
 pub const PADDING_FACTOR: i32 = 4;
 
+/// Movement shorter than this does not turn the player.
+const MIN_FACING_DELTA: f64 = 0.001;
+
+lazy! {
+    /// Colors players get tinted with, picked from the client id so every client agrees on them.
+    static PLAYER_COLORS: [RgbColor; 8] = [
+        "#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4", "#42d4f4", "#f032e6",
+    ]
+    .map(|c| parse_color(c).unwrap().alpha(110));
+}
+
 pub struct ClientPlayer {
     pos: TileUnit,
     pub(crate) camera: Camera,
     pub data: ClientDataPacket,
     pub speed: f64,
     pub reach: SingleTileUnit,
+    /// Only used for other players, positions received from the server waiting to be shown.
+    snapshots: SnapshotBuffer,
+    /// Only used for the local player, movement the server did not confirm yet.
+    prediction: MovementPrediction,
+    /// Direction of the last movement, the sprite is rotated to face it.
+    facing: Orientation,
 }
 
 impl ClientPlayer {
@@ -35,6 +61,9 @@ impl ClientPlayer {
             data,
             speed: 20.0,
             reach: 7.0,
+            snapshots: SnapshotBuffer::new(),
+            prediction: MovementPrediction::new(),
+            facing: Orientation::North,
         }
     }
 
@@ -45,6 +74,7 @@ impl ClientPlayer {
     }
 
     pub fn move_by(&mut self, by: TileUnit, tile_size: i32) {
+        self.face(by);
         self.pos.0 += by.0;
         self.pos.1 += by.1;
         self.update_cam(tile_size);
@@ -55,6 +85,72 @@ impl ClientPlayer {
         self.update_cam(tile_size);
     }
 
+    /// Keeps the player on the same spot of the screen when the tile size changes.
+    pub fn rescale(&mut self, old_tile_size: i32, tile_size: i32) {
+        let (old_x, old_y) = resolve_unit(self.pos, old_tile_size);
+        let on_screen = (old_x - self.camera.pos.0, old_y - self.camera.pos.1);
+        let (x, y) = resolve_unit(self.pos, tile_size);
+        self.camera.pos = (x - on_screen.0, y - on_screen.1);
+        self.camera.update();
+        self.update_cam(tile_size);
+    }
+
+    /// Moves the local player right away and remembers the input until the server confirms it.
+    pub fn predict_move(&mut self, by: TileUnit, tile_size: i32) {
+        self.move_by(by, tile_size);
+        self.prediction.record(by, self.pos);
+    }
+
+    /// Applies a position the server sent for the local player, replaying inputs it has not seen yet.
+    pub fn correct(&mut self, server: TileUnit, tile_size: i32) {
+        if let Some(pos) = self.prediction.reconcile(self.pos, server) {
+            self.move_to(pos, tile_size);
+        }
+    }
+
+    /// Fades out the visual part of the last correction, called once per frame.
+    pub fn smooth_correction(&mut self, dt: f64) {
+        self.prediction.smooth(dt);
+    }
+
+    /// Queues a position of a remote player, it gets shown once [`ClientPlayer::interpolate`] reaches it.
+    pub fn push_snapshot(&mut self, pos: TileUnit) {
+        self.snapshots.push(pos);
+    }
+
+    /// Moves a remote player to where it was `delay` ago.
+    pub fn interpolate(&mut self, delay: Duration) {
+        if let Some(pos) = self.snapshots.sample(delay) {
+            self.face((pos.0 - self.pos.0, pos.1 - self.pos.1));
+            self.pos = pos;
+        }
+    }
+
+    fn face(&mut self, delta: TileUnit) {
+        if delta.0.abs().max(delta.1.abs()) < MIN_FACING_DELTA {
+            return;
+        }
+        self.facing = if delta.0.abs() > delta.1.abs() {
+            if delta.0 > 0.0 {
+                Orientation::East
+            } else {
+                Orientation::West
+            }
+        } else if delta.1 > 0.0 {
+            Orientation::North
+        } else {
+            Orientation::South
+        };
+    }
+
+    /// Sprite of this player, tinted in the color of its client id and facing where it last moved.
+    /// The profile carries no appearance yet, so the color can't be picked by the player.
+    fn fill(&self) -> Fill {
+        let index = self.data.client_id % PLAYER_COLORS.len() as u64;
+        let color = PLAYER_COLORS[index as usize].clone();
+        Fill::TintedDrawable(Drawable::Texture(R.texture.player), self.facing, color)
+    }
+
     pub fn broadcast_position(&self, client: &mut FactoryIslandClient) {
         client.send(ServerBoundPacket::PlayerMove(PlayerMovePacket {
             pos: self.pos,
@@ -96,16 +192,18 @@ impl ClientPlayer {
     pub fn data_packet(&mut self, packet: PlayerDataPacket, tile_size: i32) {
         self.reach = packet.reach;
         self.pos = packet.pos;
+        self.prediction.reset();
         self.update_cam(tile_size)
     }
 
     pub fn draw(&self, ctx: &mut impl WideRenderContext, tile_size: i32) {
-        let fill = Fill::Drawable(Drawable::Texture(R.texture.player), Orientation::North);
+        let fill = self.fill();
         let z = ctx.next_z();
+        let offset = self.prediction.offset();
         drawutils::draw_in_world(
             ctx,
             &self.camera.view_area,
-            self.pos,
+            (self.pos.0 + offset.0, self.pos.1 + offset.1),
             (1.0, 1.0),
             fill,
             tile_size,
@@ -119,7 +217,7 @@ impl ClientPlayer {
         view_area: &SimpleRect,
         tile_size: i32,
     ) {
-        let fill = Fill::Drawable(Drawable::Texture(R.texture.player), Orientation::North);
+        let fill = self.fill();
         let z = ctx.next_z();
         drawutils::draw_in_world(ctx, view_area, self.pos, (1.0, 1.0), fill, tile_size, z);
     }
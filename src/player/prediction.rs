@@ -0,0 +1,119 @@
+use api::world::TileUnit;
+use log::trace;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Inputs younger than this are assumed to still be on their way to the server when a correction arrives.
+/// The move packets carry no sequence number, so the server cannot tell us which input it processed last.
+const IN_FLIGHT: Duration = Duration::from_millis(150);
+/// Positions closer than this count as the same.
+const EPSILON: f64 = 0.01;
+/// Corrections further away than this are teleports and are not smoothed.
+const SNAP_DISTANCE: f64 = 5.0;
+/// Fraction of the remaining visual correction removed per second.
+const SMOOTHING: f64 = 10.0;
+const MAX_PENDING: usize = 256;
+
+struct PendingInput {
+    seq: u32,
+    delta: TileUnit,
+    /// Where we predicted to be after this input.
+    pos: TileUnit,
+    sent_at: Instant,
+}
+
+/// Local movement that the server did not confirm yet. When the server disagrees with us,
+/// the inputs it has not seen yet are replayed on top of its position.
+pub struct MovementPrediction {
+    next_seq: u32,
+    pending: VecDeque<PendingInput>,
+    /// Difference between where the player is drawn and where it actually is, fades out over time.
+    offset: TileUnit,
+}
+
+impl MovementPrediction {
+    pub fn new() -> Self {
+        Self {
+            next_seq: 0,
+            pending: VecDeque::new(),
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /// Remembers an input that was applied locally and sent to the server.
+    pub fn record(&mut self, delta: TileUnit, pos: TileUnit) {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        if self.pending.len() >= MAX_PENDING {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(PendingInput {
+            seq,
+            delta,
+            pos,
+            sent_at: Instant::now(),
+        });
+    }
+
+    /// Applies an authoritative position. Returns the corrected position if the server disagreed with us.
+    pub fn reconcile(&mut self, current: TileUnit, server: TileUnit) -> Option<TileUnit> {
+        //the server agrees with one of our predictions, everything up to it is confirmed
+        if let Some(i) = self.pending.iter().rposition(|p| close(p.pos, server)) {
+            trace!("Server confirmed movement input {}", self.pending[i].seq);
+            self.pending.drain(..=i);
+            return None;
+        }
+        if self.pending.is_empty() && close(current, server) {
+            return None;
+        }
+
+        let now = Instant::now();
+        while let Some(front) = self.pending.front() {
+            if now.duration_since(front.sent_at) < IN_FLIGHT {
+                break;
+            }
+            self.pending.pop_front();
+        }
+
+        let mut pos = server;
+        for input in self.pending.iter_mut() {
+            pos.0 += input.delta.0;
+            pos.1 += input.delta.1;
+            input.pos = pos;
+        }
+
+        let diff = (current.0 - pos.0, current.1 - pos.1);
+        if diff.0.hypot(diff.1) > SNAP_DISTANCE {
+            self.reset();
+        } else {
+            self.offset.0 += diff.0;
+            self.offset.1 += diff.1;
+        }
+        Some(pos)
+    }
+
+    /// Forgets every pending input, used when the server moves us somewhere else on purpose.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.offset = (0.0, 0.0);
+    }
+
+    /// Lets the visual correction fade out and returns what is left of it.
+    pub fn smooth(&mut self, dt: f64) -> TileUnit {
+        let keep = (1.0 - SMOOTHING * dt).clamp(0.0, 1.0);
+        self.offset.0 *= keep;
+        self.offset.1 *= keep;
+        if self.offset.0.hypot(self.offset.1) < EPSILON {
+            self.offset = (0.0, 0.0);
+        }
+        self.offset
+    }
+
+    pub fn offset(&self) -> TileUnit {
+        self.offset
+    }
+}
+
+fn close(a: TileUnit, b: TileUnit) -> bool {
+    (a.0 - b.0).abs() < EPSILON && (a.1 - b.1).abs() < EPSILON
+}
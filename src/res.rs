@@ -53,9 +53,25 @@ r! {
             <tileset name="conveyor" atlas="textures/tiles/conveyor.png" width="64" height="64" count="4">
                 <entry name="base" index="0"/>
             </tileset>
+            <tileset name="conveyor_curve" atlas="textures/tiles/conveyor_curve.png" width="64" height="64" count="4">
+                <entry name="base" index="0"/>
+            </tileset>
+            <tileset name="conveyor_curve_mirrored" atlas="textures/tiles/conveyor_curve_mirrored.png" width="64" height="64" count="4">
+                <entry name="base" index="0"/>
+            </tileset>
+            <tileset name="conveyor_t_junction" atlas="textures/tiles/conveyor_t_junction.png" width="64" height="64" count="4">
+                <entry name="base" index="0"/>
+            </tileset>
+            <tileset name="conveyor_end" atlas="textures/tiles/conveyor_end.png" width="64" height="64" count="4">
+                <entry name="base" index="0"/>
+            </tileset>
         </tilesets>
         <animations>
             <animation name="conveyor" tileset="conveyor" range=".." fps="24"/>
+            <animation name="conveyor_curve" tileset="conveyor_curve" range=".." fps="24"/>
+            <animation name="conveyor_curve_mirrored" tileset="conveyor_curve_mirrored" range=".." fps="24"/>
+            <animation name="conveyor_t_junction" tileset="conveyor_t_junction" range=".." fps="24"/>
+            <animation name="conveyor_end" tileset="conveyor_end" range=".." fps="24"/>
         </animations>
         <drawables>
             <drawable name="bg" type="texture" ref="bg"/>
@@ -3,7 +3,8 @@ use crate::ingredients::CLIENT_INGREDIENT_REG;
 use crate::uistyles;
 use api::ingredients::IngredientStack;
 use api::inventory::{InventoryData, InventoryOwner, ItemAction};
-use api::server::packets::inventory::InventoryDataPacket;
+use api::server::packets::inventory::{InventoryDataPacket, ItemActionPacket};
+use api::server::ServerBoundPacket;
 use mvengine::graphics::Drawable;
 use mvengine::modify_style;
 use mvengine::ui::context::UiContext;
@@ -16,12 +17,21 @@ use mvengine::ui::styles;
 use mvengine::window::Window;
 use mvengine_proc::{resolve_resource, style_expr_empty, ui};
 use crate::res::R;
+use crate::gameloop::FactoryIslandClient;
+use crate::input;
+use crate::ui::display::tooltip::Tooltip;
+use log::debug;
+use mvengine::input::consts::MouseButton;
+use mvengine::ui::elements::events::UiClickAction;
+use mvutils::thread::ThreadSafe;
 
 pub struct InventoryDisplay {
     allowed_actions: ItemAction,
     data: InventoryData,
     owner: InventoryOwner,
     element: Option<Element>,
+    /// One element per stack, indexed like `data.stacks`.
+    slots: Vec<ThreadSafe<Element>>,
     player_inv: Option<Box<InventoryDisplay>>,
 }
 // в В т Т ь Ь ч Ч к К п П р Р д Д ж Ж ф Ф ы Ы у У н Н г Г ш Ш я Я м М и И б Б ю Ю э Э х Х з З
@@ -30,10 +40,12 @@ impl InventoryDisplay {
     pub fn new(mut data: InventoryDataPacket) -> Self {
         let player_inv = data.player_inventory.map(|inv_data| {
             Box::new(InventoryDisplay {
-                allowed_actions: data.item_actions,
+                //the opened inventory's restrictions don't apply to our own items
+                allowed_actions: ItemAction::TAKE | ItemAction::PUT,
                 data: inv_data,
                 owner: InventoryOwner::Player,
                 element: None,
+                slots: vec![],
                 player_inv: None,
             })
         });
@@ -42,6 +54,7 @@ impl InventoryDisplay {
             data: data.data,
             owner: data.owner,
             element: None,
+            slots: vec![],
             player_inv,
         }
     }
@@ -72,16 +85,70 @@ impl InventoryDisplay {
         let e = div.get_mut();
 
         let mut iter = self.data.stacks.iter();
-        while let Some(row) = Self::create_slot_row(&mut iter, ctx.clone(), self.data.width as usize) {
+        self.slots.clear();
+        while let Some((row, slots)) = Self::create_slot_row(&mut iter, ctx.clone(), self.data.width as usize) {
             e.add_child(row.to_child());
+            self.slots.extend(slots.into_iter().map(ThreadSafe::new));
         }
 
         div
     }
 
+    fn can_take(&self) -> bool {
+        self.allowed_actions.contains(ItemAction::TAKE)
+    }
+
+    fn can_put(&self) -> bool {
+        self.allowed_actions.contains(ItemAction::PUT)
+    }
+
+    fn amount_at(&self, slot: usize) -> u32 {
+        self.data.stacks.get(slot).map_or(0, |stack| stack.amount)
+    }
+
+    /// Name and description of the ingredient in a slot, for its tooltip.
+    fn describe_slot(&self, slot: usize) -> Option<Vec<String>> {
+        let stack = self.data.stacks.get(slot)?;
+        let ingredient = CLIENT_INGREDIENT_REG.reference_object(stack.ingredient)?;
+        let mut lines = vec![format!("{} x{}", ingredient.label(), stack.amount)];
+        if let Some(description) = ingredient.description() {
+            lines.push(description.to_string());
+        }
+        Some(lines)
+    }
+
+    fn hovered_slot(&self) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| slot.as_ref().get().state().is_hovered)
+    }
+
+    /// Every press and release on a slot this frame.
+    fn slot_events(&self) -> Vec<(usize, MouseButton, UiClickAction)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| {
+                let elem = slot.as_ref().get();
+                let event = elem.state().events.click_event.as_ref()?;
+                Some((i, event.button.clone(), event.base.action.clone()))
+            })
+            .collect()
+    }
+
+    fn set_highlight(&self, slot: usize, highlighted: bool) {
+        if let Some(elem) = self.slots.get(slot) {
+            let style = if highlighted {
+                style_expr_empty!("border.resource: color; border.color: yellow;")
+            } else {
+                style_expr_empty!("border.resource: none;")
+            };
+            elem.as_ref().get_mut().style_mut().merge_at_set_of(&style);
+        }
+    }
+
     fn create_slot(stack: &IngredientStack, ctx: UiContext) -> Element {
         let id = stack.ingredient;
-        println!("ingredient id: {id}");
         let no_border = style_expr_empty!("border.resource: none;");
         let (tex, color, border_style) = if let Some(ing) = CLIENT_INGREDIENT_REG.create_object(id) {
             let (color, border) = if let Some(color) = ing.override_bg {
@@ -96,13 +163,20 @@ impl InventoryDisplay {
 
         let mut style = uistyles::SLOT_INNER_STYLE.clone();
         style.merge_at_set_of(&style_expr_empty!("background.texture: {tex.clone()};"));
+        style.merge_at_set_of(&uistyles::SLOT_COUNT_STYLE);
         let mut outer_style = uistyles::SLOT_OUTER_STYLE.clone();
         outer_style.merge_at_set_of(&style_expr_empty!("background.color: {color.clone()}"));
         outer_style.merge_at_set_of(&border_style);
+        //single items don't need a count
+        let count = if stack.amount > 1 {
+            stack.amount.to_string()
+        } else {
+            String::new()
+        };
         let btn = ui! {
             <Ui context={ctx}>
                 <Div style={outer_style}>
-                    <Div style={style}/>
+                    <Button style={style}>{count}</Button>
                 </Div>
             </Ui>
         };
@@ -120,7 +194,7 @@ impl InventoryDisplay {
         btn
     }
 
-    fn create_slot_row(iter: &mut Iter<IngredientStack>, ctx: UiContext, slots_across: usize) -> Option<Element> {
+    fn create_slot_row(iter: &mut Iter<IngredientStack>, ctx: UiContext, slots_across: usize) -> Option<(Element, Vec<Element>)> {
         let mut div_style = uistyles::CLEAR.clone();
         div_style.merge_at_set_of(&style_expr_empty!("direction: horizontal; padding: none; margin: none;"));
 
@@ -131,11 +205,13 @@ impl InventoryDisplay {
         };
 
         let e = div.get_mut();
+        let mut slots = vec![];
 
         for i in 0..slots_across {
             if let Some(next) = iter.next() {
                 let slot = Self::create_slot(next, ctx.clone());
-                e.add_child(slot.to_child());
+                e.add_child(slot.clone().to_child());
+                slots.push(slot);
             } else {
                 if i == 0 {
                     return None;
@@ -145,16 +221,39 @@ impl InventoryDisplay {
             }
         }
 
-        Some(div)
+        Some((div, slots))
     }
 }
 
 unsafe impl Send for InventoryDisplay {}
 unsafe impl Sync for InventoryDisplay {}
 
+/// Which of the two shown inventories a slot belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Side {
+    Opened,
+    Player,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct SlotRef {
+    side: Side,
+    slot: usize,
+}
+
+/// A slot a mouse button went down on, released on another slot it becomes a drag.
+struct SlotPress {
+    from: SlotRef,
+    right: bool,
+}
+
 pub struct CurrentInvDisplay {
     root: Element,
-    elem: Option<InventoryDisplay>
+    elem: Option<InventoryDisplay>,
+    pressed: Option<SlotPress>,
+    /// Slot picked up by a click, the next click on another slot moves it there.
+    selected: Option<SlotRef>,
+    tooltip: Tooltip,
 }
 
 impl CurrentInvDisplay {
@@ -166,11 +265,16 @@ impl CurrentInvDisplay {
                 </Ui>>
             },
             elem: None,
+            pressed: None,
+            selected: None,
+            tooltip: Tooltip::new(window),
         };
         window.ui_mut().add_root(t.root.clone());
+        t.tooltip.open(window);
         t
     }
 
+    /// Shows an inventory, this is also how the server's updates replace what is shown.
     pub fn open(&mut self, to: InventoryDisplay, window: &mut Window) {
         let ui = window.ui_mut();
         if let Some(this) = self.elem.take() {
@@ -184,6 +288,154 @@ impl CurrentInvDisplay {
             this.add_child(elem.to_child());
         }
         self.elem = Some(to);
+        //slots may have moved, anything started on the old contents is stale
+        self.pressed = None;
+        self.selected = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.elem.is_some()
+    }
+
+    pub fn close(&mut self) {
+        if self.elem.take().is_some() {
+            self.root.get_mut().remove_all_children();
+        }
+        self.pressed = None;
+        self.selected = None;
+        self.tooltip.hide();
+    }
+
+    fn side(&self, side: Side) -> Option<&InventoryDisplay> {
+        let opened = self.elem.as_ref()?;
+        match side {
+            Side::Opened => Some(opened),
+            Side::Player => opened.player_inv.as_deref(),
+        }
+    }
+
+    fn other(side: Side) -> Side {
+        match side {
+            Side::Opened => Side::Player,
+            Side::Player => Side::Opened,
+        }
+    }
+
+    pub fn check_events(&mut self, window: &Window, client: &mut FactoryIslandClient) {
+        let mut events = vec![];
+        for side in [Side::Opened, Side::Player] {
+            if let Some(display) = self.side(side) {
+                for (slot, button, action) in display.slot_events() {
+                    events.push((SlotRef { side, slot }, button, action));
+                }
+            }
+        }
+
+        let hovered = [Side::Opened, Side::Player].into_iter().find_map(|side| {
+            let display = self.side(side)?;
+            display.describe_slot(display.hovered_slot()?)
+        });
+        match hovered {
+            Some(lines) => self
+                .tooltip
+                .show(lines, (window.input.mouse_x, window.input.mouse_y)),
+            None => self.tooltip.hide(),
+        }
+
+        let quick_move = window.input.is_action(input::QUICK_MOVE);
+        for (slot, button, action) in events {
+            let right = button == MouseButton::Right;
+            if !right && button != MouseButton::Left {
+                continue;
+            }
+            if action == UiClickAction::Click {
+                self.pressed = Some(SlotPress { from: slot, right });
+            } else if action == UiClickAction::Release {
+                let Some(press) = self.pressed.take() else {
+                    continue;
+                };
+                if press.right == right {
+                    self.on_release(press, slot, quick_move, client);
+                }
+            }
+        }
+    }
+
+    fn on_release(&mut self, press: SlotPress, at: SlotRef, quick_move: bool, client: &mut FactoryIslandClient) {
+        let from = press.from;
+        let amount = self.side(from.side).map_or(0, |d| d.amount_at(from.slot));
+
+        if amount == 0 && (from != at || press.right) {
+            //there is nothing to drag or split out of an empty slot
+            self.deselect();
+            return;
+        }
+
+        if from != at {
+            //dragging moves everything, right dragging splits off half
+            let amount = if press.right { (amount / 2).max(1) } else { amount };
+            self.send_move(client, from, at.side, Some(at.slot), amount);
+            self.deselect();
+            return;
+        }
+
+        if press.right {
+            //right clicking a slot splits half of it into a free slot of the same inventory
+            self.send_move(client, from, from.side, None, (amount / 2).max(1));
+        } else if quick_move {
+            self.send_move(client, from, Self::other(from.side), None, amount);
+        } else if let Some(selected) = self.selected {
+            if selected != at {
+                let amount = self.side(selected.side).map_or(0, |d| d.amount_at(selected.slot));
+                self.send_move(client, selected, at.side, Some(at.slot), amount);
+            }
+            self.deselect();
+            return;
+        } else if amount > 0 {
+            self.selected = Some(at);
+            if let Some(display) = self.side(at.side) {
+                display.set_highlight(at.slot, true);
+            }
+            return;
+        }
+        self.deselect();
+    }
+
+    fn deselect(&mut self) {
+        if let Some(selected) = self.selected.take() {
+            if let Some(display) = self.side(selected.side) {
+                display.set_highlight(selected.slot, false);
+            }
+        }
+    }
+
+    /// Asks the server to move `amount` items, `to_slot` None lets the server pick a free slot.
+    /// Nothing is changed locally, the server answers with the updated inventory.
+    fn send_move(
+        &self,
+        client: &mut FactoryIslandClient,
+        from: SlotRef,
+        to: Side,
+        to_slot: Option<usize>,
+        amount: u32,
+    ) {
+        if amount == 0 {
+            return;
+        }
+        let (Some(source), Some(target)) = (self.side(from.side), self.side(to)) else {
+            return;
+        };
+        if !source.can_take() || !target.can_put() {
+            debug!("Item action not allowed by this inventory");
+            return;
+        }
+        client.send(ServerBoundPacket::ItemAction(ItemActionPacket {
+            from: source.owner.clone(),
+            from_slot: from.slot as u32,
+            to: target.owner.clone(),
+            to_slot: to_slot.map(|slot| slot as u32),
+            amount,
+        }));
     }
 }
 
@@ -1,14 +1,17 @@
 pub mod chat;
 pub mod inventory;
+pub mod nametags;
+pub mod tooltip;
 
-use crate::world::tiles::impls::CLIENT_TILE_REG;
+use crate::ui::display::tooltip::Tooltip;
+use crate::ui::TextFocus;
+use crate::world::tiles::impls::{tile_template, TileCategory, CLIENT_TILE_REG};
 use api::server::packets::common::TileKind;
 use mvengine::color::RgbColor;
 use mvengine::graphics::Drawable;
-use mvengine::input::consts::MouseButton;
+use mvengine::ui::context::UiContext;
 use mvengine::ui::elements::button::Button;
 use mvengine::ui::elements::child::ToChildFromIterator;
-use mvengine::ui::elements::events::UiClickAction;
 use mvengine::ui::elements::prelude::*;
 use mvengine::ui::elements::Element;
 use mvengine::ui::styles::enums::BackgroundRes;
@@ -18,7 +21,9 @@ use mvengine::{expect_element_by_id, modify_style};
 use mvengine_proc::style_expr;
 use mvengine_proc::ui;
 use mvutils::lazy;
+use mvutils::state::State;
 use mvutils::thread::ThreadSafe;
+use ropey::Rope;
 
 lazy! {
     static SELECT_STYLE: UiStyle = {
@@ -36,88 +41,170 @@ lazy! {
     };
 }
 
+/// Tile buttons per row of the palette.
+const ROW_SIZE: usize = 5;
+/// How many of the last selected tiles are pinned above the palette.
+const RECENT_TILES: usize = 5;
+
+struct PaletteEntry {
+    kind: TileKind,
+    name: String,
+    category: TileCategory,
+    button: ThreadSafe<Element>,
+}
+
+/// Palette of every tile the server knows, with category tabs, a name filter and recently used tiles pinned on top.
 pub struct TileSelection {
-    selected_index: Option<usize>,
-    tiles: Vec<TileKind>,
+    selected: Option<TileKind>,
+    entries: Vec<PaletteEntry>,
+    recent: Vec<(TileKind, ThreadSafe<Element>)>,
+    tabs: Vec<(Option<TileCategory>, ThreadSafe<Element>)>,
+    tab: Option<TileCategory>,
+    filter: State<Rope>,
+    filter_focus: TextFocus,
+    last_filter: String,
     root: ThreadSafe<Element>,
-    buttons: Vec<ThreadSafe<Element>>,
+    container: ThreadSafe<Element>,
+    recent_row: ThreadSafe<Element>,
+    tooltip: Tooltip,
+    context: ThreadSafe<UiContext>,
 }
 
 impl TileSelection {
-    pub fn new(window: &Window, mut available_tiles: impl Iterator<Item = TileKind>) -> Self {
-        let tiles = available_tiles
+    pub fn new(window: &Window, available_tiles: impl Iterator<Item = TileKind>) -> Self {
+        let context = window.ui().context();
+        let entries = available_tiles
             .filter(|tile| *tile != 0)
+            .map(|kind| {
+                let (name, category) = tile_template(kind)
+                    .map_or((format!("Tile #{kind}"), TileCategory::Misc), |t| {
+                        (t.label(), t.category)
+                    });
+                PaletteEntry {
+                    kind,
+                    name,
+                    category,
+                    button: ThreadSafe::new(Self::create_button(context.clone(), kind)),
+                }
+            })
             .collect::<Vec<_>>();
-        let mut available_tiles = tiles.clone().into_iter();
+
+        let filter = State::new(Rope::new());
         let outer = ui! {
-            <Ui context={window.ui().context()}>
+            <Ui context={context.clone()}>
                 <Div id="tile_selection" style="origin: bottom_right; position: absolute; x: 100%; y: 0; height: 10cm; direction: vertical; margin: none; background.resource: color; background.color: #00000044; border.resource: none;">
+                    <Div id="tile_tabs" style="padding: none; margin: none; margin.right: 0.5cm; background.resource: none; border.resource: none;"/>
+                    <TextBox id="tile_filter" content={filter.clone()} style="margin: none; margin.right: 0.5cm; padding: none; width: 100%; height: 0.8cm; border.resource: none; background.color: #00000044; text.color: white; text.align_x: start; text.size: 100%;"/>
+                    <Div id="recent_tiles" style="padding: none; margin: none; margin.right: 0.5cm; background.resource: none; border.resource: none;"/>
                     <Div id="button_container" style="direction: vertical; padding: none; margin: none; margin.right: 0.5cm; background.resource: none; border.resource: none;"/>
                 </Div>
             </Ui>
         };
 
-        let mut container = expect_element_by_id!(outer, "button_container");
+        let container = expect_element_by_id!(outer, "button_container");
+        let recent_row = expect_element_by_id!(outer, "recent_tiles");
+        let mut tab_row = expect_element_by_id!(outer, "tile_tabs");
+        let filter_box = expect_element_by_id!(outer, "tile_filter");
 
-        let mut buttons = vec![];
-        while let Ok((bns, r)) = Self::create_row(window, &mut available_tiles) {
-            container.get_mut().add_child(r.to_child());
-            buttons.extend(bns);
+        //only show tabs for categories that have tiles
+        let categories = TileCategory::ALL
+            .into_iter()
+            .filter(|c| entries.iter().any(|e| e.category == *c))
+            .map(Some);
+        let mut tabs = vec![];
+        for category in std::iter::once(None).chain(categories) {
+            let label = category.map_or("All", TileCategory::name).to_string();
+            let tab = ui! {
+                <Ui context={context.clone()}>
+                    <Button style="height: 0.8cm; margin: 1bc; padding: none; background.resource: color; background.color: #00000066; border.resource: none; text.color: white; text.size: 100%;">{label}</Button>
+                </Ui>
+            };
+            tab_row.get_mut().add_child(tab.clone().to_child());
+            tabs.push((category, ThreadSafe::new(tab)));
         }
 
-        let buttons = buttons.into_iter().map(|x| ThreadSafe::new(x)).collect();
-
-        Self {
-            selected_index: None,
+        let mut this = Self {
+            selected: None,
+            entries,
+            recent: vec![],
+            tabs,
+            tab: None,
+            filter,
+            filter_focus: TextFocus::new(filter_box),
+            last_filter: String::new(),
             root: ThreadSafe::new(outer),
-            buttons,
-            tiles,
-        }
-    }
-
-    fn create_row(
-        window: &Window,
-        available_tiles: &mut impl Iterator<Item = TileKind>,
-    ) -> Result<(Vec<Element>, Element), ()> {
-        let buttons = match available_tiles.next_chunk::<5>() {
-            Ok(chunk) => chunk.map(|tile| Self::create_button(window, tile)).to_vec(),
-            Err(part_chunk) => {
-                if part_chunk.is_empty() {
-                    return Err(());
-                } else {
-                    part_chunk
-                        .map(|tile| Self::create_button(window, tile))
-                        .collect::<Vec<_>>()
-                }
-            }
+            container: ThreadSafe::new(container),
+            recent_row: ThreadSafe::new(recent_row),
+            tooltip: Tooltip::new(window),
+            context: ThreadSafe::new(context),
         };
+        this.rebuild();
+        this
+    }
 
-        let elem = ui! {
-            <Ui context={window.ui().context()}>
+    fn create_row(ctx: UiContext, buttons: Vec<Element>) -> Element {
+        ui! {
+            <Ui context={ctx}>
                 <Div style="background.resource: none; border.resource: none; margin: none; padding: none;">
-                    {buttons.clone().into_iter()}
+                    {buttons.into_iter()}
                 </Div>
             </Ui>
-        };
-
-        Ok((buttons, elem))
+        }
     }
 
-    fn create_button(window: &Window, tile_kind: TileKind) -> Element {
+    fn create_button(ctx: UiContext, tile_kind: TileKind) -> Element {
         let drawable = get_drawable(&tile_kind);
 
         let elem = ui! {
-            <Ui context={window.ui().context()}>
+            <Ui context={ctx}>
                 <Button style="width: 1.5cm; height: 1.5cm; background.resource: texture; background.texture: {drawable.clone()}; margin: 1bc;"/>
             </Ui>
         };
         elem
     }
 
+    /// Lays out every tile of the current tab that matches the filter.
+    fn rebuild(&mut self) {
+        let filter = self.last_filter.to_lowercase();
+        let visible = self
+            .entries
+            .iter()
+            .filter(|e| self.tab.map_or(true, |tab| tab == e.category))
+            .filter(|e| e.name.to_lowercase().contains(&filter))
+            .map(|e| e.button.as_ref().clone())
+            .collect::<Vec<_>>();
+
+        let container = self.container.get_mut();
+        container.remove_all_children();
+        for row in visible.chunks(ROW_SIZE) {
+            let row = Self::create_row(self.context.as_ref().clone(), row.to_vec());
+            container.add_child(row.to_child());
+        }
+    }
+
+    fn rebuild_recent(&mut self) {
+        let buttons = self
+            .recent
+            .iter()
+            .map(|(_, button)| button.as_ref().clone())
+            .collect::<Vec<_>>();
+        let row = self.recent_row.get_mut();
+        row.remove_all_children();
+        if !buttons.is_empty() {
+            row.add_child(Self::create_row(self.context.as_ref().clone(), buttons).to_child());
+        }
+    }
+
+    /// Whether the name filter has the keyboard focus.
+    pub fn is_typing(&self) -> bool {
+        self.filter_focus.is_focused()
+    }
+
     pub fn open(&self, window: &mut Window, mut parent: Element) {
         parent
             .get_mut()
             .add_child(self.root.as_ref().clone().to_child());
+        self.tooltip.open(window);
     }
 
     pub fn close(&mut self, window: &mut Window) {
@@ -127,40 +214,87 @@ impl TileSelection {
         }
 
         window.ui_mut().remove_root(self.root.as_ref().clone());
+        self.tooltip.close(window);
     }
 
-    pub fn check_events(&mut self) {
-        for (i, button) in self.buttons.iter().enumerate() {
-            let elem = button.as_ref().get_mut();
-            if let Some(event) = &elem.state().events.click_event {
-                if let UiClickAction::Click = event.base.action {
-                    if let MouseButton::Left = event.button {
-                        if let Some(prev) = self.selected_index {
-                            let prev_btn = &self.buttons[prev];
-                            prev_btn
-                                .as_ref()
-                                .get_mut()
-                                .style_mut()
-                                .merge_at_set_of(&NO_SELECT_STYLE);
-                            if prev == i {
-                                self.selected_index = None;
-                                return;
-                            }
-                        }
-                        self.selected_index = Some(i);
-                        elem.style_mut().merge_at_set_of(&SELECT_STYLE);
-                    }
-                }
+    pub fn check_events(&mut self, window: &Window) {
+        self.filter_focus.update(window);
+        let clicked_tab = self
+            .tabs
+            .iter()
+            .find(|(_, tab)| tab.was_left_clicked())
+            .map(|(category, _)| *category);
+        if let Some(tab) = clicked_tab {
+            self.tab = tab;
+            self.rebuild();
+        }
+
+        let filter = self.filter.read().to_string();
+        if filter != self.last_filter {
+            self.last_filter = filter;
+            self.rebuild();
+        }
+
+        let clicked = self
+            .entries
+            .iter()
+            .map(|e| (e.kind, &e.button))
+            .chain(self.recent.iter().map(|(kind, button)| (*kind, button)))
+            .find(|(_, button)| button.was_left_clicked())
+            .map(|(kind, _)| kind);
+        if let Some(kind) = clicked {
+            //clicking the selected tile again deselects it
+            if self.selected == Some(kind) {
+                self.select(None);
+            } else {
+                self.select(Some(kind));
             }
         }
+
+        let hovered = self
+            .entries
+            .iter()
+            .map(|e| (e.kind, &e.button))
+            .chain(self.recent.iter().map(|(kind, button)| (*kind, button)))
+            .find(|(_, button)| button.as_ref().get().state().is_hovered)
+            .and_then(|(kind, _)| self.entries.iter().find(|e| e.kind == kind));
+        match hovered {
+            Some(entry) => self.tooltip.show(
+                vec![entry.name.clone()],
+                (window.input.mouse_x, window.input.mouse_y),
+            ),
+            None => self.tooltip.hide(),
+        }
     }
 
-    pub fn selected_tile(&self) -> Option<&TileKind> {
-        if let Some(idx) = self.selected_index {
-            Some(&self.tiles[idx])
-        } else {
-            None
+    /// Selects a tile kind as if its button was clicked, unknown kinds clear the selection.
+    pub fn select(&mut self, kind: Option<TileKind>) {
+        self.selected = kind.filter(|kind| self.entries.iter().any(|e| e.kind == *kind));
+        if let Some(kind) = self.selected {
+            self.recent.retain(|(k, _)| *k != kind);
+            let button = Self::create_button(self.context.as_ref().clone(), kind);
+            self.recent.insert(0, (kind, ThreadSafe::new(button)));
+            self.recent.truncate(RECENT_TILES);
+            self.rebuild_recent();
         }
+
+        let buttons = self
+            .entries
+            .iter()
+            .map(|e| (e.kind, &e.button))
+            .chain(self.recent.iter().map(|(kind, button)| (*kind, button)));
+        for (kind, button) in buttons {
+            let style = if self.selected == Some(kind) {
+                &*SELECT_STYLE
+            } else {
+                &*NO_SELECT_STYLE
+            };
+            button.as_ref().get_mut().style_mut().merge_at_set_of(style);
+        }
+    }
+
+    pub fn selected_tile(&self) -> Option<&TileKind> {
+        self.selected.as_ref()
     }
 }
 
@@ -0,0 +1,105 @@
+use crate::drawutils;
+use crate::player::ClientPlayer;
+use mvengine::modify_style;
+use mvengine::net::server::ClientId;
+use mvengine::ui::context::UiContext;
+use mvengine::ui::elements::prelude::*;
+use mvengine::ui::elements::Element;
+use mvengine::ui::geometry::SimpleRect;
+use mvengine::ui::styles::UiValue;
+use mvengine::window::Window;
+use mvengine_proc::style_expr;
+use mvengine_proc::ui;
+use mvutils::hashers::U64IdentityHasher;
+use mvutils::thread::ThreadSafe;
+use std::collections::HashMap;
+
+/// Width of a name tag in pixels, tags are centered above the player.
+const TAG_WIDTH: i32 = 300;
+/// Gap between the top of the player and its name tag in pixels.
+const TAG_GAP: i32 = 4;
+
+/// Names floating above the other players.
+pub struct NameTags {
+    root: ThreadSafe<Element>,
+    tags: HashMap<ClientId, ThreadSafe<Element>, U64IdentityHasher>,
+    context: ThreadSafe<UiContext>,
+}
+
+impl NameTags {
+    pub fn new(window: &Window) -> Self {
+        let root = ui! {
+            <Ui context={window.ui().context()}>
+                <Div id="name_tags" style="position: absolute; x: 0; y: 0; width: 100%; height: 100%; padding: none; margin: none; background.resource: none; border.resource: none;"/>
+            </Ui>
+        };
+
+        Self {
+            root: ThreadSafe::new(root),
+            tags: HashMap::with_hasher(U64IdentityHasher::default()),
+            context: ThreadSafe::new(window.ui().context()),
+        }
+    }
+
+    pub fn open(&self, window: &mut Window) {
+        window.ui_mut().add_root(self.root.as_ref().clone());
+    }
+
+    pub fn close(&self, window: &mut Window) {
+        window.ui_mut().remove_root(self.root.as_ref().clone());
+    }
+
+    pub fn add(&mut self, id: ClientId, name: String) {
+        let tag = ui! {
+            <Ui context={self.context.as_ref().clone()}>
+                <Button style="position: absolute; height: 0.6cm; margin: none; padding: none; background.resource: color; background.color: #00000066; border.resource: none; text.color: white; text.size: 100%; text.align_x: middle; text.align_y: middle;">{name}</Button>
+            </Ui>
+        };
+        {
+            let elem = tag.get_mut();
+            let style = elem.style_mut();
+            modify_style!(style.width = UiValue::Just(TAG_WIDTH));
+        }
+        if self.tags.insert(id, ThreadSafe::new(tag.clone())).is_some() {
+            self.rebuild();
+        } else {
+            self.root.get_mut().add_child(tag.to_child());
+        }
+    }
+
+    pub fn remove(&mut self, id: ClientId) {
+        if self.tags.remove(&id).is_some() {
+            self.rebuild();
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let root = self.root.get_mut();
+        root.remove_all_children();
+        for tag in self.tags.values() {
+            root.add_child(tag.as_ref().clone().to_child());
+        }
+    }
+
+    /// Moves every tag above its player, needs to happen after the players moved.
+    pub fn update(
+        &mut self,
+        view_area: &SimpleRect,
+        tile_size: i32,
+        players: &HashMap<ClientId, ClientPlayer, U64IdentityHasher>,
+    ) {
+        for (id, tag) in &self.tags {
+            let Some(player) = players.get(id) else {
+                continue;
+            };
+            let (x, y) = drawutils::get_screen_pos(view_area, player.pos(), tile_size);
+            let elem = tag.as_ref().get_mut();
+            let style = elem.style_mut();
+            modify_style!(style.x = UiValue::Just(x + tile_size / 2 - TAG_WIDTH / 2));
+            modify_style!(style.y = UiValue::Just(y + tile_size + TAG_GAP));
+        }
+    }
+}
+
+unsafe impl Send for NameTags {}
+unsafe impl Sync for NameTags {}
@@ -0,0 +1,94 @@
+use mvengine::modify_style;
+use mvengine::ui::context::UiContext;
+use mvengine::ui::elements::prelude::*;
+use mvengine::ui::elements::Element;
+use mvengine::ui::styles::UiValue;
+use mvengine::window::Window;
+use mvengine_proc::style_expr;
+use mvengine_proc::ui;
+use mvutils::thread::ThreadSafe;
+
+/// Default distance between the cursor and the tooltip in pixels.
+const CURSOR_OFFSET: i32 = 16;
+
+/// A few lines of text following the cursor.
+pub struct Tooltip {
+    root: ThreadSafe<Element>,
+    lines: Vec<String>,
+    offset: (i32, i32),
+    context: ThreadSafe<UiContext>,
+}
+
+impl Tooltip {
+    pub fn new(window: &Window) -> Self {
+        let root = ui! {
+            <Ui context={window.ui().context()}>
+                <Div style="position: absolute; x: 0; y: 0; direction: vertical; padding: none; margin: none; background.resource: none; border.resource: none;"/>
+            </Ui>
+        };
+
+        Self {
+            root: ThreadSafe::new(root),
+            lines: vec![],
+            offset: (CURSOR_OFFSET, CURSOR_OFFSET),
+            context: ThreadSafe::new(window.ui().context()),
+        }
+    }
+
+    /// Moves the tooltip relative to the cursor, so several tooltips don't cover each other.
+    pub fn with_offset(mut self, x: i32, y: i32) -> Self {
+        self.offset = (x, y);
+        self
+    }
+
+    pub fn open(&self, window: &mut Window) {
+        window.ui_mut().add_root(self.root.as_ref().clone());
+    }
+
+    pub fn close(&self, window: &mut Window) {
+        window.ui_mut().remove_root(self.root.as_ref().clone());
+    }
+
+    /// Shows the lines next to the cursor, the elements are only rebuilt when the text changed.
+    pub fn show(&mut self, lines: Vec<String>, mouse: (i32, i32)) {
+        if lines != self.lines {
+            self.lines = lines;
+            self.rebuild();
+        }
+        let elem = self.root.get_mut();
+        let style = elem.style_mut();
+        modify_style!(style.x = UiValue::Just(mouse.0 + self.offset.0));
+        modify_style!(style.y = UiValue::Just(mouse.1 + self.offset.1));
+    }
+
+    pub fn hide(&mut self) {
+        if !self.lines.is_empty() {
+            self.lines.clear();
+            self.rebuild();
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let root = self.root.get_mut();
+        root.remove_all_children();
+        if self.lines.is_empty() {
+            return;
+        }
+        let lines = self.lines.iter().cloned().map(|line| {
+            ui! {
+                <Ui context={self.context.as_ref().clone()}>
+                    <Button style="height: 0.5cm; margin: none; padding: none; background.resource: none; border.resource: none; text.color: white; text.size: 100%; text.align_x: start;">{line}</Button>
+                </Ui>
+            }
+        });
+        let panel = ui! {
+            <Ui context={self.context.as_ref().clone()}>
+                <Div style="direction: vertical; margin: none; padding: 2mm; background.resource: color; background.color: #000000aa; border.resource: none;">{lines}</Div>
+            </Ui>
+        };
+        root.add_child(panel.to_child());
+    }
+}
+
+unsafe impl Send for Tooltip {}
+unsafe impl Sync for Tooltip {}
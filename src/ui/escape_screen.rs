@@ -16,9 +16,6 @@ use mvengine_proc::ui;
 use mvutils::thread::ThreadSafe;
 use std::any::Any;
 use std::process::exit;
-use api::inventory::InventoryOwner;
-use api::server::packets::inventory::InventoryOpenPacket;
-use api::server::ServerBoundPacket;
 
 pub struct EscapeScreen {
     elem: ThreadSafe<Element>,
@@ -82,17 +79,12 @@ impl GameUiCallbacks for EscapeScreen {
         };
 
         if self.settings_btn.was_left_clicked() {
-            //game_handler.ui_manager.goto(UI_SETTINGS_SCREEN, window);
-            if let Some(client) = &mut game_handler.client {
-                client.send(ServerBoundPacket::InventoryOpenPacket(
-                    InventoryOpenPacket {
-                        owner: InventoryOwner::Player,
-                    },
-                ));
-            }
+            game_handler.ui_manager.goto(UI_SETTINGS_SCREEN, window);
         }
         
         if self.quit_btn.was_left_clicked() {
+            game_handler.game.store_world_data();
+            game_handler.game.save_settings();
             if let Some(client) = &mut game_handler.client {
                 client.disconnect(DisconnectReason::Disconnected);
             }
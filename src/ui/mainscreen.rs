@@ -117,6 +117,7 @@ impl GameUiCallbacks for Mainscreen {
             );
             if let Some(client) = conn {
                 game_handler.client = Some(client);
+                game_handler.game.server_name = ip;
 
                 game_handler.ui_manager.close_all(window);
             } else {
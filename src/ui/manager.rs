@@ -2,6 +2,7 @@ use crate::game::Game;
 use crate::gameloop::GameHandler;
 use crate::ui::escape_screen::EscapeScreen;
 use crate::ui::mainscreen::Mainscreen;
+use crate::ui::map_screen::MapScreen;
 use crate::ui::settings::SettingsScreen;
 use crate::ui::status_screen::StatusScreen;
 use crate::ui::GameUi;
@@ -9,12 +10,13 @@ use log::warn;
 use mvengine::ui::elements::UiElementStub;
 use mvengine::window::Window;
 
-pub const AMT_UIS: usize = 4;
+pub const AMT_UIS: usize = 5;
 
 pub const UI_MAIN_SCREEN: usize = 0;
 pub const UI_ESCAPE_SCREEN: usize = 1;
 pub const UI_SETTINGS_SCREEN: usize = 2;
 pub const UI_STATUS_SCREEN: usize = 3;
+pub const UI_MAP_SCREEN: usize = 4;
 
 pub struct GameUiManager {
     current: Option<usize>,
@@ -32,6 +34,7 @@ impl GameUiManager {
                 GameUi::new(SettingsScreen::new(window, game))
                     .expect("vanilla stuff that cannot break"),
                 GameUi::new(StatusScreen::new(window)).expect("vanilla stuff that cannot break"),
+                GameUi::new(MapScreen::new(window)).expect("vanilla stuff that cannot break"),
             ],
         };
 
@@ -52,6 +55,14 @@ impl GameUiManager {
         }
     }
 
+    pub fn is_open(&self, ui: usize) -> bool {
+        self.current == Some(ui)
+    }
+
+    pub fn any_open(&self) -> bool {
+        self.current.is_some()
+    }
+
     pub fn close_all(&mut self, window: &mut Window) {
         window.ui_mut().page_manager_mut().close_all();
         self.current = None;
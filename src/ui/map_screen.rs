@@ -0,0 +1,169 @@
+use crate::game::worldmap::WorldMap;
+use crate::gameloop::GameHandler;
+use crate::ui::{GameUiCallbacks, TextFocus};
+use crate::uistyles;
+use mvengine::expect_element_by_id;
+use mvengine::expect_inner_element_by_id_mut;
+use mvengine::ui::attributes::UiState;
+use mvengine::ui::elements::prelude::*;
+use mvengine::ui::elements::Element;
+use mvengine::ui::page::Page;
+use mvengine::window::Window;
+use mvengine_proc::{style_expr_empty, ui};
+use mvutils::state::State;
+use mvutils::thread::ThreadSafe;
+use ropey::Rope;
+use std::any::Any;
+
+/// Full screen map of everything explored on the current server.
+/// The map itself is drawn by the world view, this page only holds the marker controls on the side.
+pub struct MapScreen {
+    elem: ThreadSafe<Element>,
+    back_btn: ThreadSafe<Element>,
+    add_btn: ThreadSafe<Element>,
+    remove_btn: ThreadSafe<Element>,
+    marker_list: ThreadSafe<Element>,
+    marker_btns: Vec<ThreadSafe<Element>>,
+    marker_name: UiState,
+    marker_focus: TextFocus,
+}
+
+impl MapScreen {
+    pub fn new(window: &Window) -> Self {
+        let mut main_style = uistyles::CLEAR.clone();
+        main_style.merge_at_set_of(&style_expr_empty!(
+            "position: absolute; width: 100%; height: 100%; margin: none; padding: none; child_align_x: end; child_align_y: start;"
+        ));
+
+        let mut side_style = uistyles::FRAME.clone();
+        side_style.merge_at_set_of(&style_expr_empty!("width: auto; height: 100%;"));
+
+        let mut list_style = uistyles::CLEAR.clone();
+        list_style.merge_at_set_of(&style_expr_empty!(
+            "direction: vertical; margin: none; padding: none; overflow_y: normal;"
+        ));
+
+        let widget_style = uistyles::PRESET.clone();
+
+        let elem = ui! {
+            <Ui context={window.ui().context()}>
+                <Div id="world_map" style={main_style}>
+                    <Div style={side_style}>
+                        <Button style={uistyles::CLEAR_PRESET.clone()}>- Map -</Button>
+                        <TextBox style={uistyles::EDIT_PRESET.clone()} id="marker_name" placeholder="Marker name"/>
+                        <Button style={widget_style.clone()} id="add_marker">Add marker</Button>
+                        <Button style={widget_style.clone()} id="remove_marker">Remove marker</Button>
+                        <Div style={list_style} id="marker_list"/>
+                        <Button style={widget_style.clone()} id="back">Back</Button>
+                    </Div>
+                </Div>
+            </Ui>
+        };
+
+        let back_btn = expect_element_by_id!(elem, "back");
+        let add_btn = expect_element_by_id!(elem, "add_marker");
+        let remove_btn = expect_element_by_id!(elem, "remove_marker");
+        let marker_list = expect_element_by_id!(elem, "marker_list");
+        let marker_box = expect_element_by_id!(elem, "marker_name");
+
+        let mut marker_name = State::new(Rope::new()).map_identity();
+        expect_inner_element_by_id_mut!(elem, TextBox, "marker_name", textbox => {
+            marker_name = textbox.get_content();
+        });
+
+        Self {
+            elem: ThreadSafe::new(elem),
+            back_btn: ThreadSafe::new(back_btn),
+            add_btn: ThreadSafe::new(add_btn),
+            remove_btn: ThreadSafe::new(remove_btn),
+            marker_list: ThreadSafe::new(marker_list),
+            marker_btns: vec![],
+            marker_name,
+            marker_focus: TextFocus::new(marker_box),
+        }
+    }
+
+    /// Whether a marker name is being typed, the map keys must not react then.
+    pub fn is_typing(&self) -> bool {
+        self.marker_focus.is_focused()
+    }
+
+    /// The screen was hidden, whatever was focused before is not anymore.
+    pub fn reset_focus(&mut self) {
+        self.marker_focus.unfocus();
+    }
+
+    /// Recreates the marker list, needs to be called whenever the markers of the map changed.
+    pub fn refresh(&mut self, window: &Window, map: &WorldMap) {
+        self.marker_list.get_mut().remove_all_children();
+        self.marker_btns.clear();
+        for marker in &map.markers {
+            let text = format!("{} ({}, {})", marker.name, marker.x, marker.y);
+            let btn = ui! {
+                <Ui context={window.ui().context()}>
+                    <Button style={uistyles::CLEAR_PRESET.clone()}>{text}</Button>
+                </Ui>
+            };
+            self.marker_list.get_mut().add_child(btn.clone().to_child());
+            self.marker_btns.push(ThreadSafe::new(btn));
+        }
+    }
+}
+
+impl Page for MapScreen {
+    fn get_elem(&self) -> Element {
+        self.elem.as_ref().clone()
+    }
+}
+
+impl GameUiCallbacks for MapScreen {
+    fn get_name(&self) -> &str {
+        "world_map"
+    }
+
+    fn check_ui_events(&mut self, window: &mut Window, game_handler: &mut GameHandler) {
+        self.marker_focus.update(window);
+        if self.back_btn.was_left_clicked() {
+            game_handler.ui_manager.close_all(window);
+            return;
+        }
+
+        let Some(view) = &mut game_handler.game.world_view else {
+            return;
+        };
+        let map = &mut view.world_map;
+
+        if let Some(i) = self.marker_btns.iter().position(|b| b.was_left_clicked()) {
+            map.focus_marker(i);
+        }
+
+        let mut changed = false;
+        if self.add_btn.was_left_clicked() {
+            let name = self.marker_name.read().to_string();
+            let name = if name.trim().is_empty() {
+                format!("Marker {}", map.markers.len() + 1)
+            } else {
+                name.trim().to_string()
+            };
+            map.add_marker(name);
+            *self.marker_name.write() = Rope::new();
+            changed = true;
+        }
+        if self.remove_btn.was_left_clicked() {
+            changed |= map.remove_marker_near_center().is_some();
+        }
+
+        if changed {
+            map.save();
+            self.refresh(window, map);
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
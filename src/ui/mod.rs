@@ -2,12 +2,15 @@ pub mod display;
 pub mod escape_screen;
 pub mod mainscreen;
 pub mod manager;
+pub mod map_screen;
 pub mod settings;
 pub mod status_screen;
 
 use crate::gameloop::GameHandler;
+use crate::input;
 use log::{debug, error};
 use mvengine::rendering::RenderContext;
+use mvengine::ui::elements::prelude::*;
 use mvengine::ui::elements::{Element, UiElementCallbacks, UiElementStub};
 use mvengine::ui::page::Page;
 use mvengine::window::Window;
@@ -50,3 +53,40 @@ pub trait GameUiCallbacks: Page + Send + Sync {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
+
+/// Keyboard focus of a text box, tracked from clicks since the text box does not report it.
+/// Clicking the box focuses it, clicking anywhere else, escape or return take the focus away again.
+/// Key presses are typed into a focused box, so key bound game actions must not react to them.
+pub struct TextFocus {
+    elem: ThreadSafe<Element>,
+    focused: bool,
+}
+
+impl TextFocus {
+    pub fn new(elem: Element) -> Self {
+        Self {
+            elem: ThreadSafe::new(elem),
+            focused: false,
+        }
+    }
+
+    /// Needs to be called every frame the text box is shown.
+    pub fn update(&mut self, window: &Window) {
+        if self.elem.was_left_clicked() {
+            self.focused = true;
+        } else if window.input.was_action(input::PLACE)
+            || window.input.was_action(input::ESCAPE)
+            || window.input.was_action(input::CHAT)
+        {
+            self.focused = false;
+        }
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focused = false;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+}
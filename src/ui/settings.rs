@@ -22,6 +22,10 @@ pub struct SettingsScreen {
     pub enable_clouds: State<bool>,
     pub enable_ssao: State<bool>,
     pub indicator_circle: State<bool>,
+    pub render_distance: State<f32>,
+    pub interpolation_delay: State<f32>,
+    pub confirm_destroy: State<bool>,
+    pub show_inspector: State<bool>,
 }
 
 impl SettingsScreen {
@@ -38,6 +42,10 @@ impl SettingsScreen {
         let enable_clouds = game.settings.cloud_shader.clone();
         let enable_ssao = game.settings.ssao_shader.clone();
         let indicator_circle = game.settings.indicator_circle.clone();
+        let render_distance = game.settings.render_distance.clone();
+        let interpolation_delay = game.settings.interpolation_delay.clone();
+        let confirm_destroy = game.settings.confirm_destroy.clone();
+        let show_inspector = game.settings.show_inspector.clone();
 
         let elem = ui! {
             <Ui context={window.ui().context()}>
@@ -48,9 +56,15 @@ impl SettingsScreen {
                             <CheckBox selected={enable_clouds.clone()} style={checkbox_style.clone()}>Cloud Shader</CheckBox>
                             <CheckBox selected={enable_ssao.clone()} style={checkbox_style.clone()}>SSAO Shader</CheckBox>
                             <CheckBox selected={indicator_circle.clone()} style={checkbox_style.clone()}>Fat indicator circle</CheckBox>
-                            <Div style={clear_style}>
+                            <CheckBox selected={confirm_destroy.clone()} style={checkbox_style.clone()}>Confirm breaking machines</CheckBox>
+                            <CheckBox selected={show_inspector.clone()} style={checkbox_style.clone()}>Describe hovered tiles</CheckBox>
+                            <Div style={clear_style.clone()}>
                                 <Button style={uistyles::CLEAR_PRESET.clone()}>Simulation distance:</Button>
-                                <Slider style={slider_style} range="1..10@1"/>
+                                <Slider style={slider_style.clone()} range="1..10@1" value={render_distance.clone()}/>
+                            </Div>
+                            <Div style={clear_style.clone()}>
+                                <Button style={uistyles::CLEAR_PRESET.clone()}>Player smoothing (ms):</Button>
+                                <Slider style={slider_style} range="0..300@10" value={interpolation_delay.clone()}/>
                             </Div>
                             <Button style={widget.clone()} id="back_btn">Back</Button>
                         </Div>
@@ -67,6 +81,10 @@ impl SettingsScreen {
             enable_clouds,
             enable_ssao,
             indicator_circle,
+            render_distance,
+            interpolation_delay,
+            confirm_destroy,
+            show_inspector,
         }
     }
 }
@@ -139,6 +139,14 @@ lazy! {
         overflow_y: never;
     });
 
+    /// Merged into the slot's texture element, the count is its text so nothing is layered over the slot.
+    pub static SLOT_COUNT_STYLE: UiStyle = multiline_str_into!(style_expr,{
+        text.color: white;
+        text.size: 40%;
+        text.align_x: end;
+        text.align_y: start;
+    });
+
     pub static INVENTORY_WRAPPER_STYLE: UiStyle = multiline_str_into!(style_expr,{
         background.resource: none;
         border.resource: none;
@@ -0,0 +1,92 @@
+use crate::world::terrain::terrain_for;
+use crate::world::tiles::impls::tile_template;
+use crate::world::{tile_pos_at, ClientWorld};
+use api::world::TileUnit;
+
+/// Size of the player's collision box in tiles, the box starts at the player position.
+pub const PLAYER_SIZE: f64 = 1.0;
+/// Keeps a box that touches a tile edge from counting as overlapping the next tile.
+const EPSILON: f64 = 1e-4;
+
+impl ClientWorld {
+    /// Whether a tile blocks movement. Unloaded tiles are walkable, the server has the final say anyway.
+    pub fn is_solid(&self, raw: (i32, i32)) -> bool {
+        let Some(terrain) = self.terrain_at(raw) else {
+            return false;
+        };
+        if terrain_for(terrain).is_some_and(|t| !t.walkable) {
+            return true;
+        }
+        if self.is_multitile_at(&tile_pos_at(raw)) {
+            return true;
+        }
+        self.tile_at(raw)
+            .and_then(|tile| tile_template(tile.id))
+            .is_some_and(|template| template.solid)
+    }
+
+    fn any_solid(
+        &self,
+        min: TileUnit,
+        max: TileUnit,
+        ignore: &[(i32, i32)],
+    ) -> Option<Vec<(i32, i32)>> {
+        let mut hits = vec![];
+        for x in tile_span(min.0, max.0) {
+            for y in tile_span(min.1, max.1) {
+                if self.is_solid((x, y)) && !ignore.contains(&(x, y)) {
+                    hits.push((x, y));
+                }
+            }
+        }
+        (!hits.is_empty()).then_some(hits)
+    }
+
+    /// Moves a box of [`PLAYER_SIZE`] at `pos` by `by`, one axis after the other, so blocked movement
+    /// slides along walls. Returns the distance actually moved.
+    /// Tiles the box already overlaps are ignored, so a tile placed on the player does not trap it.
+    pub fn resolve_movement(&self, pos: TileUnit, by: TileUnit) -> TileUnit {
+        let inside = self
+            .any_solid(pos, (pos.0 + PLAYER_SIZE, pos.1 + PLAYER_SIZE), &[])
+            .unwrap_or_default();
+
+        let mut x = pos.0 + by.0;
+        if by.0 != 0.0 {
+            let hits = self.any_solid(
+                (x, pos.1),
+                (x + PLAYER_SIZE, pos.1 + PLAYER_SIZE),
+                &inside,
+            );
+            if let Some(hits) = hits {
+                x = if by.0 > 0.0 {
+                    let wall = hits.iter().map(|h| h.0).min().unwrap_or_default();
+                    (wall as f64 - PLAYER_SIZE).max(pos.0)
+                } else {
+                    let wall = hits.iter().map(|h| h.0).max().unwrap_or_default();
+                    (wall as f64 + 1.0).min(pos.0)
+                };
+            }
+        }
+
+        let mut y = pos.1 + by.1;
+        if by.1 != 0.0 {
+            let hits = self.any_solid((x, y), (x + PLAYER_SIZE, y + PLAYER_SIZE), &inside);
+            if let Some(hits) = hits {
+                y = if by.1 > 0.0 {
+                    let wall = hits.iter().map(|h| h.1).min().unwrap_or_default();
+                    (wall as f64 - PLAYER_SIZE).max(pos.1)
+                } else {
+                    let wall = hits.iter().map(|h| h.1).max().unwrap_or_default();
+                    (wall as f64 + 1.0).min(pos.1)
+                };
+            }
+        }
+
+        (x - pos.0, y - pos.1)
+    }
+}
+
+/// Raw tile coordinates covered by the range from `min` to `max` on one axis.
+fn tile_span(min: f64, max: f64) -> std::ops::RangeInclusive<i32> {
+    (min + EPSILON).floor() as i32..=(max - EPSILON).floor() as i32
+}
@@ -0,0 +1,156 @@
+use crate::drawutils;
+use crate::res::R;
+use crate::world::tiles::ClientDrawable;
+use api::world::tiles::Orientation;
+use mvengine::color::RgbColor;
+use mvengine::graphics::animation::GlobalAnimation;
+use mvengine::graphics::Drawable;
+use mvengine::math::vec::Vec4;
+use mvengine::rendering::texture::Texture;
+use mvengine::ui::geometry::SimpleRect;
+use mvengine::ui::rendering::WideRenderContext;
+use std::ops::Deref;
+
+/// A quad that was resolved once when the chunk geometry got rebuilt. The rect is in world pixels.
+struct CachedQuad {
+    rect: SimpleRect,
+    texture: &'static Texture,
+    uv: [(f32, f32); 4],
+    orientation: Orientation,
+    z: i32,
+    tint: Option<RgbColor>,
+    part: [f32; 4],
+}
+
+/// Fractions of the full texture, as (x0, y0, x1, y1).
+pub const FULL: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+/// Prebuilt quad batch of a single chunk. It is only rebuilt when the chunk was marked dirty or
+/// the tile size changed, animated quads just get their uvs refreshed every frame.
+pub struct ChunkGeometry {
+    dirty: bool,
+    tile_size: i32,
+    quads: Vec<CachedQuad>,
+    animated: Vec<(usize, &'static GlobalAnimation<'static>)>,
+    custom: Vec<(usize, i32, Option<RgbColor>)>,
+}
+
+impl ChunkGeometry {
+    pub fn new() -> Self {
+        Self {
+            dirty: true,
+            tile_size: 0,
+            quads: vec![],
+            animated: vec![],
+            custom: vec![],
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn needs_rebuild(&self, tile_size: i32) -> bool {
+        self.dirty || self.tile_size != tile_size
+    }
+
+    pub fn begin(&mut self, tile_size: i32) {
+        self.quads.clear();
+        self.animated.clear();
+        self.custom.clear();
+        self.tile_size = tile_size;
+        self.dirty = false;
+    }
+
+    pub fn push(
+        &mut self,
+        drawable: &Drawable,
+        orientation: Orientation,
+        rect: SimpleRect,
+        z: i32,
+        tint: Option<RgbColor>,
+    ) {
+        self.push_part(drawable, orientation, rect, FULL, z, tint);
+    }
+
+    /// Pushes a quad that only shows the given part of the drawable, used for terrain transitions.
+    pub fn push_part(
+        &mut self,
+        drawable: &Drawable,
+        orientation: Orientation,
+        rect: SimpleRect,
+        part: [f32; 4],
+        z: i32,
+        tint: Option<RgbColor>,
+    ) {
+        let drawable = ClientDrawable::from_drawable(drawable.clone(), R.deref().deref());
+        let (texture, uv) = drawable.get_texture();
+        if let ClientDrawable::Animation(anim) = drawable {
+            self.animated.push((self.quads.len(), anim));
+        }
+        self.quads.push(CachedQuad {
+            rect,
+            texture,
+            uv: quad_uv(uv, orientation, part),
+            orientation,
+            z,
+            tint,
+            part,
+        });
+    }
+
+    /// Tiles with a custom drawer cannot be cached, their chunk indices and depth are kept to call the drawer each frame.
+    /// The drawer knows nothing about predictions, so a tint is laid over whatever it drew.
+    pub fn push_custom(&mut self, index: usize, z: i32, tint: Option<RgbColor>) {
+        self.custom.push((index, z, tint));
+    }
+
+    pub fn custom(&self) -> &[(usize, i32, Option<RgbColor>)] {
+        &self.custom
+    }
+
+    pub fn tick_animations(&mut self) {
+        for (index, anim) in &self.animated {
+            let (texture, uv) = anim.get_current();
+            let quad = &mut self.quads[*index];
+            quad.texture = texture;
+            quad.uv = quad_uv(uv, quad.orientation, quad.part);
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut impl WideRenderContext, view_area: &SimpleRect) {
+        for quad in &self.quads {
+            if view_area.intersects(&quad.rect) {
+                drawutils::tile_rect(
+                    ctx,
+                    quad.uv,
+                    &quad.rect,
+                    view_area,
+                    quad.texture,
+                    quad.z,
+                    quad.tint.clone(),
+                );
+            }
+        }
+    }
+}
+
+fn quad_uv(uv: Vec4, orientation: Orientation, part: [f32; 4]) -> [(f32, f32); 4] {
+    let full = orientation.apply(Texture::get_uv_inner_static(uv));
+    if part == FULL {
+        return full;
+    }
+    //bilinear interpolation over the corners, which are ordered (x0, y0), (x0, y1), (x1, y1), (x1, y0)
+    let lerp = |fx: f32, fy: f32| {
+        let w = [(1.0 - fx) * (1.0 - fy), (1.0 - fx) * fy, fx * fy, fx * (1.0 - fy)];
+        let mut u = 0.0;
+        let mut v = 0.0;
+        for i in 0..4 {
+            u += full[i].0 * w[i];
+            v += full[i].1 * w[i];
+        }
+        (u, v)
+    };
+    let [x0, y0, x1, y1] = part;
+    [lerp(x0, y0), lerp(x0, y1), lerp(x1, y1), lerp(x1, y0)]
+}
@@ -0,0 +1,120 @@
+use crate::world::terrain::get_terrain_map_color;
+use crate::world::ClientChunk;
+use api::world::chunk::{Chunk, CHUNK_TILES};
+use api::world::tiles::TileKind;
+use api::world::{ChunkPos, CHUNK_SIZE};
+use mvengine::color::parse::parse_color;
+use mvengine::color::RgbColor;
+use mvutils::lazy;
+
+const TILE_BIT: u16 = 1 << 15;
+const MULTITILE_BIT: u16 = 1 << 14;
+const TERRAIN_MASK: u16 = MULTITILE_BIT - 1;
+
+lazy! {
+    static TILE_COLOR: RgbColor = parse_color("#d0d0d0").unwrap();
+    static MULTITILE_COLOR: RgbColor = parse_color("#c07a2c").unwrap();
+}
+
+/// A horizontal run of equally colored tiles, in chunk local tile coordinates.
+#[derive(Clone)]
+pub struct MapRun {
+    pub x: i32,
+    pub y: i32,
+    pub len: i32,
+    pub color: RgbColor,
+}
+
+/// Map view of a single chunk. Each cell stores the terrain id and whether a tile or multitile is on it,
+/// cells are laid out row by row in chunk local coordinates.
+#[derive(Clone)]
+pub struct MapChunk {
+    pub cells: Box<[u16]>,
+    pub runs: Vec<MapRun>,
+}
+
+impl MapChunk {
+    pub fn from_cells(cells: Box<[u16]>) -> Self {
+        let mut this = Self {
+            cells,
+            runs: vec![],
+        };
+        this.rebuild_runs();
+        this
+    }
+
+    pub fn from_client_chunk(pos: ChunkPos, chunk: &ClientChunk) -> Self {
+        let mut cells = vec![0u16; CHUNK_TILES].into_boxed_slice();
+        for i in 0..CHUNK_TILES {
+            cells[Self::cell_index(pos, i)] = Self::cell_of(chunk, i);
+        }
+        Self::from_cells(cells)
+    }
+
+    /// Cell index of a chunk tile index.
+    pub fn cell_index(pos: ChunkPos, index: usize) -> usize {
+        let tile_pos = Chunk::position_from_index(&pos, index);
+        let local = (
+            tile_pos.raw.0 - pos.0 * CHUNK_SIZE,
+            tile_pos.raw.1 - pos.1 * CHUNK_SIZE,
+        );
+        (local.1 * CHUNK_SIZE + local.0) as usize
+    }
+
+    /// The cell of a chunk tile, tile state does not show on the map.
+    pub fn cell_of(chunk: &ClientChunk, index: usize) -> u16 {
+        let mut cell = chunk.terrain[index].id as u16 & TERRAIN_MASK;
+        if chunk.occupancy[index].is_some() {
+            cell |= MULTITILE_BIT;
+        } else if chunk.tiles[index].is_some() {
+            cell |= TILE_BIT;
+        }
+        cell
+    }
+
+    /// Changes a single cell and rebuilds only the runs of its row. Returns false if nothing changed.
+    pub fn set_cell(&mut self, cell_index: usize, cell: u16) -> bool {
+        if self.cells[cell_index] == cell {
+            return false;
+        }
+        self.cells[cell_index] = cell;
+        let y = cell_index as i32 / CHUNK_SIZE;
+        self.runs.retain(|run| run.y != y);
+        self.push_row_runs(y);
+        true
+    }
+
+    fn cell_color(cell: u16) -> RgbColor {
+        if cell & MULTITILE_BIT != 0 {
+            MULTITILE_COLOR.clone()
+        } else if cell & TILE_BIT != 0 {
+            TILE_COLOR.clone()
+        } else {
+            get_terrain_map_color((cell & TERRAIN_MASK) as TileKind)
+        }
+    }
+
+    pub fn rebuild_runs(&mut self) {
+        self.runs.clear();
+        for y in 0..CHUNK_SIZE {
+            self.push_row_runs(y);
+        }
+    }
+
+    fn push_row_runs(&mut self, y: i32) {
+        let mut x = 0;
+        while x < CHUNK_SIZE {
+            let cell = self.cells[(y * CHUNK_SIZE + x) as usize];
+            let start = x;
+            while x < CHUNK_SIZE && self.cells[(y * CHUNK_SIZE + x) as usize] == cell {
+                x += 1;
+            }
+            self.runs.push(MapRun {
+                x: start,
+                y,
+                len: x - start,
+                color: Self::cell_color(cell),
+            });
+        }
+    }
+}
@@ -1,59 +1,252 @@
+pub mod collision;
+pub mod geometry;
+pub mod map;
 pub mod multitiles;
-pub mod terrain_tex_mapper;
+pub mod prediction;
+pub mod terrain;
 pub mod tiles;
 
 use crate::drawutils;
 use crate::drawutils::Fill;
-use crate::world::multitiles::ClientMultiTilePlacement;
-use crate::world::tiles::{LoadedClientTile, TileDraw};
+use crate::world::geometry::ChunkGeometry;
+use crate::world::multitiles::{ClientMultiTilePlacement, MultiTileRef};
+use crate::world::prediction::PredictionTable;
+use crate::world::terrain::{get_terrain_layer, terrain_for, CORNERS, SIDES};
+use crate::world::tiles::impls::tile_template;
+use crate::world::tiles::LoadedClientTile;
+use api::player::uuid::UUID;
 use api::server::packets::world::{ChunkDataPacket, TileSetPacket};
 use api::world::chunk::{Chunk, CHUNK_TILES};
 use api::world::tiles::pos::TilePos;
 use api::world::tiles::{Orientation, TileKind};
 use api::world::{ChunkPos, CHUNK_SIZE};
 use mvengine::graphics::Drawable;
-use mvengine::rendering::RenderContext;
 use mvengine::ui::geometry::SimpleRect;
 use mvengine::ui::rendering::WideRenderContext;
 use std::collections::HashMap;
+use std::mem;
 
 pub struct ClientWorld {
     loaded: HashMap<ChunkPos, ClientChunk>,
+    predictions: PredictionTable,
 }
 
 pub struct ClientChunk {
     pub terrain: Box<[LoadedClientTile]>,
     pub tiles: Box<[Option<LoadedClientTile>]>,
     pub multitiles: Vec<ClientMultiTilePlacement>,
+    /// Maps every tile index to the multitile covering it, including placements owned by neighbouring chunks.
+    pub occupancy: Box<[Option<MultiTileRef>]>,
+    pub geometry: ChunkGeometry,
+}
+
+/// Resolves a raw tile coordinate into its chunk position and index.
+pub fn tile_pos_at(raw: (i32, i32)) -> TilePos {
+    let chunk_pos = (raw.0.div_euclid(CHUNK_SIZE), raw.1.div_euclid(CHUNK_SIZE));
+    let local = (raw.0.rem_euclid(CHUNK_SIZE), raw.1.rem_euclid(CHUNK_SIZE));
+    Chunk::position_from_index(&chunk_pos, (local.1 * CHUNK_SIZE + local.0) as usize)
 }
 
 impl ClientWorld {
     pub fn new() -> Self {
         Self {
             loaded: HashMap::new(),
+            predictions: PredictionTable::new(),
         }
     }
 
     pub fn is_multitile_at(&self, pos: &TilePos) -> bool {
-        for chunk_pos in pos.multitile_chunk_maybe_positions() {
-            if let Some(chunk) = self.loaded.get(&chunk_pos) {
-                if chunk.multitiles.iter().any(|mt| mt.includes(pos)) {
-                    return true;
+        self.multitile_ref_at(pos).is_some()
+    }
+
+    fn multitile_ref_at(&self, pos: &TilePos) -> Option<&MultiTileRef> {
+        let chunk = self.loaded.get(&pos.chunk_pos)?;
+        chunk.occupancy[Chunk::get_index(pos)].as_ref()
+    }
+
+    pub fn multitile_at(&self, pos: &TilePos) -> Option<&ClientMultiTilePlacement> {
+        let mt_ref = self.multitile_ref_at(pos)?;
+        let owner = self.loaded.get(&mt_ref.owner)?;
+        owner.multitiles.iter().find(|mt| mt.uuid == mt_ref.uuid)
+    }
+
+    /// Writes the placement into the occupancy tables of all loaded chunks it covers.
+    fn occupy(&mut self, owner: ChunkPos, placement_index: usize) {
+        let Some(placement) = self
+            .loaded
+            .get(&owner)
+            .and_then(|c| c.multitiles.get(placement_index))
+        else {
+            return;
+        };
+        let (origin, extent, uuid) = (placement.pos.raw, placement.extent, placement.uuid.clone());
+        for x in origin.0..origin.0 + extent.0 {
+            for y in origin.1..origin.1 + extent.1 {
+                let pos = tile_pos_at((x, y));
+                if let Some(chunk) = self.loaded.get_mut(&pos.chunk_pos) {
+                    chunk.occupancy[Chunk::get_index(&pos)] = Some(MultiTileRef {
+                        owner,
+                        uuid: uuid.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn vacate(&mut self, placement: &ClientMultiTilePlacement) {
+        let (origin, extent) = (placement.pos.raw, placement.extent);
+        for x in origin.0..origin.0 + extent.0 {
+            for y in origin.1..origin.1 + extent.1 {
+                let pos = tile_pos_at((x, y));
+                if let Some(chunk) = self.loaded.get_mut(&pos.chunk_pos) {
+                    let slot = &mut chunk.occupancy[Chunk::get_index(&pos)];
+                    if slot.as_ref().is_some_and(|r| r.uuid == placement.uuid) {
+                        *slot = None;
+                    }
                 }
             }
         }
-        false
+    }
+
+    pub fn get_chunk(&self, pos: ChunkPos) -> Option<&ClientChunk> {
+        self.loaded.get(&pos)
     }
 
     pub fn get_chunk_mut(&mut self, pos: ChunkPos) -> Option<&mut ClientChunk> {
         self.loaded.get_mut(&pos)
     }
 
+    pub fn terrain_at(&self, raw: (i32, i32)) -> Option<TileKind> {
+        let pos = tile_pos_at(raw);
+        let chunk = self.loaded.get(&pos.chunk_pos)?;
+        Some(chunk.terrain[Chunk::get_index(&pos)].id)
+    }
+
+    pub fn tile_at(&self, raw: (i32, i32)) -> Option<&LoadedClientTile> {
+        let pos = tile_pos_at(raw);
+        let chunk = self.loaded.get(&pos.chunk_pos)?;
+        chunk.tiles[Chunk::get_index(&pos)].as_ref()
+    }
+
+    /// Recomputes the linked variant of a linkable tile from its current neighbours.
+    fn relink(&mut self, raw: (i32, i32)) {
+        let pos = tile_pos_at(raw);
+        let index = Chunk::get_index(&pos);
+        let link = {
+            let Some(tile) = self.tile_at(raw) else {
+                return;
+            };
+            let Some(linking) = tile_template(tile.id).and_then(|t| t.linking.as_ref()) else {
+                return;
+            };
+            let mut mask = 0;
+            for (bit, (dx, dy)) in SIDES {
+                if let Some(neighbour) = self.tile_at((raw.0 + dx, raw.1 + dy)) {
+                    if (linking.rule)(tile, neighbour, bit) {
+                        mask |= bit;
+                    }
+                }
+            }
+            linking.connect(mask, tile.orientation)
+        };
+        if let Some(chunk) = self.loaded.get_mut(&pos.chunk_pos) {
+            if let Some(tile) = &mut chunk.tiles[index] {
+                let changed = tile
+                    .link
+                    .as_ref()
+                    .is_none_or(|l| l.mask != link.mask || l.shape != link.shape);
+                tile.link = Some(link);
+                if changed {
+                    chunk.geometry.mark_dirty();
+                }
+            }
+        }
+    }
+
+    fn relink_around(&mut self, pos: &TilePos) {
+        let raw = pos.raw;
+        self.relink(raw);
+        for (_, (dx, dy)) in SIDES {
+            self.relink((raw.0 + dx, raw.1 + dy));
+        }
+    }
+
+    /// Relinks every tile of the chunk together with the ring of tiles around it.
+    fn relink_chunk(&mut self, pos: ChunkPos) {
+        let origin = (pos.0 * CHUNK_SIZE, pos.1 * CHUNK_SIZE);
+        for x in -1..=CHUNK_SIZE {
+            for y in -1..=CHUNK_SIZE {
+                self.relink((origin.0 + x, origin.1 + y));
+            }
+        }
+    }
+
+    /// Collects every neighbouring terrain kind that spills over the given tile, together with the mask of sides and corners it touches.
+    fn terrain_transitions(&self, pos: &TilePos, own: TileKind) -> Vec<(TileKind, u8)> {
+        let own_priority = terrain_for(own).map_or(0, |t| t.priority());
+        let sides = SIDES.into_iter();
+        let corners = CORNERS.into_iter().map(|(bit, offset, _)| (bit, offset));
+        let mut transitions: Vec<(TileKind, u8)> = vec![];
+        for (bit, (dx, dy)) in sides.chain(corners) {
+            let Some(neighbour) = self.terrain_at((pos.raw.0 + dx, pos.raw.1 + dy)) else {
+                continue;
+            };
+            if neighbour == own {
+                continue;
+            }
+            let Some(terrain) = terrain_for(neighbour) else {
+                continue;
+            };
+            if terrain.blend.is_none() || terrain.priority() <= own_priority {
+                continue;
+            }
+            if let Some(entry) = transitions.iter_mut().find(|(k, _)| *k == neighbour) {
+                entry.1 |= bit;
+            } else {
+                transitions.push((neighbour, bit));
+            }
+        }
+        transitions
+    }
+
+    /// Marks the chunk and all of its neighbours dirty, since multitiles can reach across chunk borders.
+    fn mark_dirty_around(&mut self, pos: ChunkPos) {
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(chunk) = self.loaded.get_mut(&(pos.0 + dx, pos.1 + dy)) {
+                    chunk.geometry.mark_dirty();
+                }
+            }
+        }
+    }
+
     pub fn sync(&mut self, packet: TileSetPacket) {
+        //a resolved prediction loses its pending tint
+        let was_pending = self.predictions.resolve(&packet.pos).is_some();
         let pos = packet.pos.chunk_pos;
         if let Some(chunk) = self.loaded.get_mut(&pos) {
-            chunk.tiles[Chunk::get_index(&packet.pos)] =
-                LoadedClientTile::from_server_tile(packet.tile, false);
+            let index = Chunk::get_index(&packet.pos);
+            let mut tile = LoadedClientTile::from_server_tile(packet.tile, false);
+            let unchanged = match (&mut tile, &chunk.tiles[index]) {
+                (Some(new), Some(old)) => {
+                    if new.id == old.id {
+                        if let (Some(new_state), Some(old_state)) = (&mut new.state, &old.state) {
+                            new_state.carry_over(old_state.as_ref());
+                        }
+                        if new.orientation == old.orientation {
+                            new.link = old.link.clone();
+                        }
+                    }
+                    new.looks_like(old)
+                }
+                (None, None) => true,
+                _ => false,
+            };
+            chunk.tiles[index] = tile;
+            if was_pending || !unchanged {
+                chunk.geometry.mark_dirty();
+            }
+            self.relink_around(&packet.pos);
         }
     }
 
@@ -84,105 +277,251 @@ impl ClientWorld {
             terrain,
             tiles,
             multitiles: multis,
+            occupancy: vec![None; CHUNK_TILES].into_boxed_slice(),
+            geometry: ChunkGeometry::new(),
         };
 
-        self.loaded.insert(packet.pos, chunk);
+        let pos = packet.pos;
+        self.predictions.resolve_chunk(pos);
+        //a resync replaces the chunk, its old placements must not linger in the neighbours' occupancy
+        if let Some(old) = self.loaded.insert(pos, chunk) {
+            for placement in &old.multitiles {
+                self.vacate(placement);
+            }
+        }
+        //neighbours may own multitiles reaching into the new chunk
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let owner = (pos.0 + dx, pos.1 + dy);
+                let amount = self.loaded.get(&owner).map_or(0, |c| c.multitiles.len());
+                for i in 0..amount {
+                    self.occupy(owner, i);
+                }
+            }
+        }
+        self.relink_chunk(pos);
+        self.mark_dirty_around(pos);
     }
 
+    /// Optimistically places a tile and records it as a pending prediction until the server answers.
     pub fn set_ghost_block(&mut self, pos: &TilePos, id: TileKind, orientation: Orientation) {
         if let Some(chunk) = self.loaded.get_mut(&pos.chunk_pos) {
             let index = Chunk::get_index(&pos);
-            if id == 0 {
-                chunk.tiles[index] = None;
+            let ghost = if id == 0 {
+                None
             } else {
-                let loaded = LoadedClientTile::new_ghost(id, orientation);
-                chunk.tiles[index] = Some(loaded);
+                Some(LoadedClientTile::new_ghost(id, orientation))
+            };
+            let previous = mem::replace(&mut chunk.tiles[index], ghost);
+            chunk.geometry.mark_dirty();
+            self.predictions.predict(pos, previous);
+            self.relink_around(pos);
+        }
+    }
+
+    /// Rolls back every prediction the server did not answer in time.
+    pub fn check_predictions(&mut self) {
+        for expired in self.predictions.take_expired() {
+            let pos = expired.pos;
+            if let Some(chunk) = self.loaded.get_mut(&pos.chunk_pos) {
+                chunk.tiles[Chunk::get_index(&pos)] = expired.authoritative;
+                chunk.geometry.mark_dirty();
+                self.relink_around(&pos);
+            }
+        }
+    }
+
+    pub fn add_multitile(&mut self, placement: ClientMultiTilePlacement) {
+        let pos = placement.pos.chunk_pos;
+        if let Some(chunk) = self.loaded.get_mut(&pos) {
+            chunk.multitiles.push(placement);
+            let index = chunk.multitiles.len() - 1;
+            self.occupy(pos, index);
+            self.mark_dirty_around(pos);
+        }
+    }
+
+    pub fn remove_multitile(&mut self, pos: ChunkPos, uuid: UUID) {
+        if let Some(chunk) = self.loaded.get_mut(&pos) {
+            let mut remove = None;
+            for i in 0..chunk.multitiles.len() {
+                if chunk.multitiles[i].uuid == uuid {
+                    remove = Some(i);
+                    break;
+                }
+            }
+            if let Some(i) = remove {
+                let placement = chunk.multitiles.remove(i);
+                self.vacate(&placement);
+                self.mark_dirty_around(pos);
             }
         }
     }
 
     pub fn drop_chunk(&mut self, pos: ChunkPos) {
-        self.loaded.remove(&pos);
+        self.predictions.resolve_chunk(pos);
+        if let Some(chunk) = self.loaded.remove(&pos) {
+            for placement in &chunk.multitiles {
+                self.vacate(placement);
+            }
+        }
+        self.relink_chunk(pos);
+        self.mark_dirty_around(pos);
     }
 
     pub fn drop_all(&mut self) {
         self.loaded.clear();
+        self.predictions.clear();
     }
 
-    pub fn draw(
-        &self,
-        renderer: &mut impl WideRenderContext,
-        view_area: &SimpleRect,
-        tile_size: i32,
-    ) {
-        for (pos, chunk) in self.loaded.iter() {
-            let chunk_area = SimpleRect::new(
-                pos.0 * CHUNK_SIZE * tile_size,
-                pos.1 * CHUNK_SIZE * tile_size,
-                CHUNK_SIZE * tile_size,
-                CHUNK_SIZE * tile_size,
+    fn chunk_area(pos: &ChunkPos, tile_size: i32) -> SimpleRect {
+        SimpleRect::new(
+            pos.0 * CHUNK_SIZE * tile_size,
+            pos.1 * CHUNK_SIZE * tile_size,
+            CHUNK_SIZE * tile_size,
+            CHUNK_SIZE * tile_size,
+        )
+    }
+
+    fn rebuild_geometry(&mut self, chunk_pos: ChunkPos, tile_size: i32) {
+        let Some(chunk) = self.loaded.get(&chunk_pos) else {
+            return;
+        };
+        let mut geometry = ChunkGeometry::new();
+        geometry.begin(tile_size);
+
+        for i in 0..chunk.terrain.len() {
+            let terrain = &chunk.terrain[i];
+            let pos = Chunk::position_from_index(&chunk_pos, i);
+            let tile_rect = SimpleRect::new(
+                pos.raw.0 * tile_size,
+                pos.raw.1 * tile_size,
+                tile_size,
+                tile_size,
             );
-            if view_area.intersects(&chunk_area) {
-                for i in 0..chunk.terrain.len() {
-                    let terrain = &chunk.terrain[i];
-                    let orientation = terrain.orientation;
-                    let pos = Chunk::position_from_index(pos, i);
+            let terrain_height = get_terrain_layer(terrain.id);
+            //void terrain has nothing to draw, tiles on top of it still do
+            if terrain.id != 0 {
+                geometry.push(
+                    &terrain.texture,
+                    terrain.orientation,
+                    tile_rect,
+                    terrain_height,
+                    None,
+                );
+                for (kind, mask) in self.terrain_transitions(&pos, terrain.id) {
+                    let Some(neighbour) = terrain_for(kind) else {
+                        continue;
+                    };
+                    for part in neighbour.transition(mask) {
+                        let [x0, y0, x1, y1] = part.area;
+                        let px = pos.raw.0 * tile_size;
+                        let py = pos.raw.1 * tile_size;
+                        let ts = tile_size as f32;
+                        let rect = SimpleRect::new(
+                            px + (x0 * ts) as i32,
+                            py + (y0 * ts) as i32,
+                            ((x1 - x0) * ts) as i32,
+                            ((y1 - y0) * ts) as i32,
+                        );
+                        geometry.push_part(
+                            &part.drawable,
+                            Orientation::North,
+                            rect,
+                            part.area,
+                            terrain_height - 1 - neighbour.priority(),
+                            None,
+                        );
+                    }
+                }
+            }
+            if self.is_multitile_at(&pos) {
+                continue;
+            }
+            let tint = self
+                .predictions
+                .is_pending(&pos)
+                .then(prediction::pending_tint);
+            if let Some(tile) = &chunk.tiles[i] {
+                if tile.drawer.is_some() {
+                    geometry.push_custom(i, terrain_height - 101, tint);
+                } else if tile.id != 0 {
                     let tile_rect = SimpleRect::new(
                         pos.raw.0 * tile_size,
                         pos.raw.1 * tile_size,
                         tile_size,
                         tile_size,
                     );
-                    if view_area.intersects(&tile_rect) {
-                        let terrain_height = 1000 - terrain.id as i32 * 100;
-                        terrain.draw(
-                            renderer,
-                            tile_size,
-                            &pos,
-                            orientation,
-                            view_area,
-                            terrain_height,
-                        );
-                        if self.is_multitile_at(&pos) {
-                            continue;
-                        }
-                        if let Some(tile) = &chunk.tiles[i] {
-                            if let Some(drawer) = tile.drawer {
-                                drawer(renderer, view_area, &pos, tile_size, tile);
-                            } else {
-                                let orientation = tile.orientation;
-                                tile.draw(
+                    let (drawable, orientation) = tile.display();
+                    geometry.push(drawable, orientation, tile_rect, terrain_height - 101, tint);
+                }
+            }
+        }
+        for multitile in &chunk.multitiles {
+            let terrain = &chunk.terrain[Chunk::get_index(&multitile.pos)];
+            let terrain_height = get_terrain_layer(terrain.id);
+            let tex = if let Some(client_mt) = &multitile.client_multi_tile {
+                client_mt.get_relevant_texture(multitile.extent.0 > multitile.extent.1)
+            } else {
+                Drawable::missing()
+            };
+            let rect = SimpleRect::new(
+                multitile.pos.raw.0 * tile_size,
+                multitile.pos.raw.1 * tile_size,
+                multitile.extent.0 * tile_size,
+                multitile.extent.1 * tile_size,
+            );
+            geometry.push(&tex, Orientation::North, rect, terrain_height - 101, None);
+        }
+
+        if let Some(chunk) = self.loaded.get_mut(&chunk_pos) {
+            chunk.geometry = geometry;
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        renderer: &mut impl WideRenderContext,
+        view_area: &SimpleRect,
+        tile_size: i32,
+    ) {
+        let dirty = self
+            .loaded
+            .iter()
+            .filter(|(pos, chunk)| {
+                chunk.geometry.needs_rebuild(tile_size)
+                    && view_area.intersects(&Self::chunk_area(pos, tile_size))
+            })
+            .map(|(pos, _)| *pos)
+            .collect::<Vec<_>>();
+        for pos in dirty {
+            self.rebuild_geometry(pos, tile_size);
+        }
+
+        for (pos, chunk) in self.loaded.iter_mut() {
+            if view_area.intersects(&Self::chunk_area(pos, tile_size)) {
+                chunk.geometry.tick_animations();
+                chunk.geometry.draw(renderer, view_area);
+                for (i, z, tint) in chunk.geometry.custom() {
+                    let (i, z) = (*i, *z);
+                    if let Some(tile) = &chunk.tiles[i] {
+                        if let Some(drawer) = tile.drawer {
+                            let pos = Chunk::position_from_index(pos, i);
+                            drawer(renderer, view_area, &pos, tile_size, tile, z);
+                            if let Some(tint) = tint {
+                                //on top of the ingredients the drawer put one layer above the tile
+                                drawutils::draw_in_world_tile(
                                     renderer,
-                                    tile_size,
-                                    &pos,
-                                    orientation,
                                     view_area,
-                                    terrain_height - 101,
+                                    pos,
+                                    Fill::Color(tint.clone()),
+                                    tile_size,
+                                    (z - 2) as f32,
                                 );
                             }
                         }
                     }
                 }
-                for multitile in &chunk.multitiles {
-                    let terrain = &chunk.terrain[Chunk::get_index(&multitile.pos)];
-                    let terrain_height = 1000 - terrain.id as i32 * 100;
-                    let tex = if let Some(client_mt) = &multitile.client_multi_tile {
-                        client_mt.get_relevant_texture(multitile.extent.0 > multitile.extent.1)
-                    } else {
-                        Drawable::missing()
-                    };
-                    let w = multitile.extent.0 as f64;
-                    let h = multitile.extent.1 as f64;
-                    drawutils::draw_in_world(
-                        renderer,
-                        view_area,
-                        multitile.pos.to_unit(),
-                        (w, h),
-                        Fill::Drawable(tex, Orientation::North),
-                        tile_size,
-                        (terrain_height - 101) as f32,
-                    );
-                }
             }
         }
     }
@@ -3,7 +3,7 @@ use api::multitile::MultiTilePlacement;
 use api::player::uuid::UUID;
 use api::registry::{Registerable, Registry};
 use api::world::tiles::pos::TilePos;
-use api::world::TileExtent;
+use api::world::{ChunkPos, TileExtent};
 use mvengine::graphics::Drawable;
 use mvutils::lazy;
 
@@ -24,6 +24,13 @@ impl ClientMultiTilePlacement {
     }
 }
 
+/// Entry of a chunk's multitile occupancy table, pointing to the chunk that owns the placement.
+#[derive(Clone)]
+pub struct MultiTileRef {
+    pub owner: ChunkPos,
+    pub uuid: UUID,
+}
+
 impl From<MultiTilePlacement> for ClientMultiTilePlacement {
     fn from(value: MultiTilePlacement) -> Self {
         Self {
@@ -0,0 +1,79 @@
+use crate::world::tiles::LoadedClientTile;
+use api::world::tiles::pos::TilePos;
+use api::world::ChunkPos;
+use mvengine::color::RgbColor;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long a ghost block may wait for the server's answer before it is rolled back.
+pub const PREDICTION_TIMEOUT: Duration = Duration::from_millis(2000);
+
+pub fn pending_tint() -> RgbColor {
+    RgbColor::yellow().alpha(90)
+}
+
+pub struct PendingPrediction {
+    pub pos: TilePos,
+    /// The tile the server last told us about, restored if the prediction times out.
+    pub authoritative: Option<LoadedClientTile>,
+    pub placed_at: Instant,
+}
+
+/// Client side placements that were sent to the server but not yet confirmed, keyed by raw tile position.
+pub struct PredictionTable {
+    pending: HashMap<(i32, i32), PendingPrediction>,
+}
+
+impl PredictionTable {
+    pub fn new() -> Self {
+        Self {
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Records a new prediction. If one already exists for that tile, the original authoritative tile is kept
+    /// and only the timer is restarted.
+    pub fn predict(&mut self, pos: &TilePos, previous: Option<LoadedClientTile>) {
+        if let Some(existing) = self.pending.get_mut(&pos.raw) {
+            existing.placed_at = Instant::now();
+        } else {
+            self.pending.insert(
+                pos.raw,
+                PendingPrediction {
+                    pos: pos.clone(),
+                    authoritative: previous,
+                    placed_at: Instant::now(),
+                },
+            );
+        }
+    }
+
+    pub fn is_pending(&self, pos: &TilePos) -> bool {
+        self.pending.contains_key(&pos.raw)
+    }
+
+    pub fn resolve(&mut self, pos: &TilePos) -> Option<PendingPrediction> {
+        self.pending.remove(&pos.raw)
+    }
+
+    pub fn resolve_chunk(&mut self, chunk_pos: ChunkPos) {
+        self.pending.retain(|_, p| p.pos.chunk_pos != chunk_pos);
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    pub fn take_expired(&mut self) -> Vec<PendingPrediction> {
+        let expired = self
+            .pending
+            .iter()
+            .filter(|(_, p)| p.placed_at.elapsed() >= PREDICTION_TIMEOUT)
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+        expired
+            .into_iter()
+            .filter_map(|k| self.pending.remove(&k))
+            .collect()
+    }
+}
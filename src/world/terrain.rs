@@ -0,0 +1,327 @@
+use crate::lang;
+use crate::res::R;
+use crate::world::geometry::FULL;
+use api::registry::{Registerable, Registry};
+use api::world::tiles::TileKind;
+use mvengine::color::parse::parse_color;
+use mvengine::color::RgbColor;
+use mvengine::graphics::Drawable;
+use mvutils::lazy;
+use std::collections::HashMap;
+
+pub const MASK_NORTH: u8 = 1;
+pub const MASK_EAST: u8 = 2;
+pub const MASK_SOUTH: u8 = 4;
+pub const MASK_WEST: u8 = 8;
+
+/// Side bit with the raw offset of the neighbour on that side.
+pub const SIDES: [(u8, (i32, i32)); 4] = [
+    (MASK_NORTH, (0, 1)),
+    (MASK_EAST, (1, 0)),
+    (MASK_SOUTH, (0, -1)),
+    (MASK_WEST, (-1, 0)),
+];
+
+pub const MASK_NORTH_EAST: u8 = 16;
+pub const MASK_SOUTH_EAST: u8 = 32;
+pub const MASK_SOUTH_WEST: u8 = 64;
+pub const MASK_NORTH_WEST: u8 = 128;
+
+/// Corner bit with the raw offset of the neighbour in that corner and the two sides next to it.
+pub const CORNERS: [(u8, (i32, i32), u8); 4] = [
+    (MASK_NORTH_EAST, (1, 1), MASK_NORTH | MASK_EAST),
+    (MASK_SOUTH_EAST, (1, -1), MASK_SOUTH | MASK_EAST),
+    (MASK_SOUTH_WEST, (-1, -1), MASK_SOUTH | MASK_WEST),
+    (MASK_NORTH_WEST, (-1, 1), MASK_NORTH | MASK_WEST),
+];
+
+/// Drops corner bits whose corner is already covered by the transition of an adjacent side,
+/// which leaves the 47 masks a transition can actually look like.
+pub fn normalize_mask(mask: u8) -> u8 {
+    let mut normalized = mask & 0xF;
+    for (bit, _, sides) in CORNERS {
+        if mask & bit != 0 && mask & sides == 0 {
+            normalized |= bit;
+        }
+    }
+    normalized
+}
+
+/// Terrain with a higher priority spills over neighbouring terrain with a lower one.
+/// The width is the fraction of the neighbouring tile that gets covered.
+#[derive(Clone, Copy)]
+pub struct TerrainBlend {
+    pub priority: i32,
+    pub width: f32,
+}
+
+/// A drawable and the part of the tile it covers, as (x0, y0, x1, y1).
+#[derive(Clone)]
+pub struct TransitionPart {
+    pub drawable: Drawable,
+    pub area: [f32; 4],
+}
+
+#[derive(Clone)]
+pub struct ClientTerrain {
+    pub id: usize,
+    /// Registry name, empty if the terrain was registered without one.
+    pub name: &'static str,
+    /// Language key of the name shown to players, empty to fall back to the registry name.
+    pub display_name: &'static str,
+    pub drawable: Drawable,
+    pub layer: i32,
+    pub blend: Option<TerrainBlend>,
+    pub map_color: RgbColor,
+    pub walkable: bool,
+    pub buildable: bool,
+    /// What this terrain draws over a neighbouring tile, indexed by the normalized mask of sides and corners it touches there.
+    transitions: Vec<Vec<TransitionPart>>,
+}
+
+impl ClientTerrain {
+    /// Name for the ui, falls back to the registry name and then the id.
+    pub fn label(&self) -> String {
+        if !self.display_name.is_empty() {
+            lang::text(self.display_name).to_string()
+        } else if !self.name.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("Terrain #{}", self.id + 1)
+        }
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.blend.map_or(0, |b| b.priority)
+    }
+
+    /// Parts drawn over a tile whose neighbours of this terrain form the given mask.
+    pub fn transition(&self, mask: u8) -> &[TransitionPart] {
+        self.transitions
+            .get(normalize_mask(mask) as usize)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Builds the transition of every mask out of side strips and corner squares of the terrain's own drawable,
+    /// unless a dedicated variant was registered for the mask.
+    fn build_transitions(
+        drawable: &Drawable,
+        blend: Option<TerrainBlend>,
+        variants: &HashMap<u8, Drawable>,
+    ) -> Vec<Vec<TransitionPart>> {
+        let Some(blend) = blend else {
+            return vec![];
+        };
+        let w = blend.width;
+        (0..=u8::MAX)
+            .map(|mask| {
+                if normalize_mask(mask) != mask {
+                    return vec![];
+                }
+                if let Some(variant) = variants.get(&mask) {
+                    return vec![TransitionPart {
+                        drawable: variant.clone(),
+                        area: FULL,
+                    }];
+                }
+                let sides = SIDES.into_iter().map(|(bit, _)| bit);
+                let corners = CORNERS.into_iter().map(|(bit, _, _)| bit);
+                sides
+                    .chain(corners)
+                    .filter(|bit| mask & bit != 0)
+                    .map(|bit| TransitionPart {
+                        drawable: drawable.clone(),
+                        area: transition_area(bit, w),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn transition_area(bit: u8, w: f32) -> [f32; 4] {
+    match bit {
+        MASK_NORTH => [0.0, 1.0 - w, 1.0, 1.0],
+        MASK_EAST => [1.0 - w, 0.0, 1.0, 1.0],
+        MASK_SOUTH => [0.0, 0.0, 1.0, w],
+        MASK_WEST => [0.0, 0.0, w, 1.0],
+        MASK_NORTH_EAST => [1.0 - w, 1.0 - w, 1.0, 1.0],
+        MASK_SOUTH_EAST => [1.0 - w, 0.0, 1.0, w],
+        MASK_SOUTH_WEST => [0.0, 0.0, w, w],
+        MASK_NORTH_WEST => [0.0, 1.0 - w, w, 1.0],
+        _ => FULL,
+    }
+}
+
+pub struct ClientTerrainCreateInfo {
+    name: &'static str,
+    display_name: &'static str,
+    drawable: Drawable,
+    layer: i32,
+    blend: Option<TerrainBlend>,
+    map_color: RgbColor,
+    walkable: bool,
+    buildable: bool,
+    variants: HashMap<u8, Drawable>,
+}
+
+impl ClientTerrainCreateInfo {
+    pub fn solid(drawable: Drawable, layer: i32) -> Self {
+        Self {
+            name: "",
+            display_name: "",
+            drawable,
+            layer,
+            blend: None,
+            map_color: RgbColor::black(),
+            walkable: true,
+            buildable: true,
+            variants: HashMap::new(),
+        }
+    }
+
+    pub fn blending(drawable: Drawable, layer: i32, priority: i32, width: f32) -> Self {
+        Self {
+            name: "",
+            display_name: "",
+            drawable,
+            layer,
+            blend: Some(TerrainBlend { priority, width }),
+            map_color: RgbColor::black(),
+            walkable: true,
+            buildable: true,
+            variants: HashMap::new(),
+        }
+    }
+
+    pub fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Language key of the name players see in the inspector.
+    pub fn display(mut self, display_name: &'static str) -> Self {
+        self.display_name = display_name;
+        self
+    }
+
+    /// Color used for this terrain on the minimap and the world map.
+    pub fn with_map_color(mut self, color: &str) -> Self {
+        self.map_color = parse_color(color).unwrap();
+        self
+    }
+
+    /// Players cannot walk on this terrain.
+    pub fn not_walkable(mut self) -> Self {
+        self.walkable = false;
+        self
+    }
+
+    /// Tiles cannot be placed on this terrain.
+    pub fn not_buildable(mut self) -> Self {
+        self.buildable = false;
+        self
+    }
+
+    /// Dedicated drawable covering a whole neighbouring tile for a transition mask,
+    /// instead of the strips and corners cut from this terrain's drawable.
+    pub fn with_transition(mut self, mask: u8, drawable: Drawable) -> Self {
+        self.variants.insert(normalize_mask(mask), drawable);
+        self
+    }
+}
+
+impl Registerable for ClientTerrain {
+    type CreateInfo = ClientTerrainCreateInfo;
+
+    fn with_id(id: usize, info: Self::CreateInfo) -> Self {
+        let transitions =
+            ClientTerrain::build_transitions(&info.drawable, info.blend, &info.variants);
+        Self {
+            id,
+            name: info.name,
+            display_name: info.display_name,
+            drawable: info.drawable,
+            layer: info.layer,
+            blend: info.blend,
+            map_color: info.map_color,
+            walkable: info.walkable,
+            buildable: info.buildable,
+            transitions,
+        }
+    }
+}
+
+lazy! {
+    pub static CLIENT_TERRAIN_REG: Registry<ClientTerrain> = Registry::new();
+}
+
+/// Server terrain ids start at 1, 0 is void.
+pub fn terrain_for(id: TileKind) -> Option<&'static ClientTerrain> {
+    if id < 1 {
+        return None;
+    }
+    CLIENT_TERRAIN_REG.reference_object(id as usize - 1)
+}
+
+pub fn get_terrain_drawable(id: TileKind) -> Drawable {
+    terrain_for(id)
+        .map(|t| t.drawable.clone())
+        .unwrap_or_else(Drawable::missing)
+}
+
+pub fn get_terrain_layer(id: TileKind) -> i32 {
+    terrain_for(id).map_or(1000, |t| t.layer)
+}
+
+pub fn get_terrain_map_color(id: TileKind) -> RgbColor {
+    terrain_for(id).map_or(RgbColor::black(), |t| t.map_color.clone())
+}
+
+pub fn register_terrain() {
+    CLIENT_TERRAIN_REG.register(
+        ClientTerrainCreateInfo::solid(
+            Drawable::Texture(R.texture.terrain_water),
+            900,
+        )
+        .named("water")
+        .display("terrain.water")
+        .with_map_color("#2f5fa8")
+        .not_walkable()
+        .not_buildable(),
+    );
+    CLIENT_TERRAIN_REG.register(
+        ClientTerrainCreateInfo::blending(
+            Drawable::Texture(R.texture.terrain_sand),
+            800,
+            1,
+            0.25,
+        )
+        .named("sand")
+        .display("terrain.sand")
+        .with_map_color("#d8c88a"),
+    );
+    CLIENT_TERRAIN_REG.register(
+        ClientTerrainCreateInfo::blending(
+            Drawable::Texture(R.texture.terrain_grass),
+            700,
+            2,
+            0.2,
+        )
+        .named("grass")
+        .display("terrain.grass")
+        .with_map_color("#4f8a3a"),
+    );
+    CLIENT_TERRAIN_REG.register(
+        ClientTerrainCreateInfo::blending(
+            Drawable::Texture(R.texture.terrain_stone),
+            600,
+            3,
+            0.15,
+        )
+        .named("stone")
+        .display("terrain.stone")
+        .with_map_color("#7a7a7a"),
+    );
+}
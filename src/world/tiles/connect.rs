@@ -0,0 +1,232 @@
+use crate::world::tiles::LoadedClientTile;
+use crate::world::terrain::{MASK_EAST, MASK_NORTH, MASK_SOUTH, MASK_WEST};
+use api::world::tiles::Orientation;
+use mvengine::graphics::Drawable;
+
+/// Decides whether a tile links to its neighbour on the given side (one of the `MASK_*` bits).
+pub type LinkRule = fn(&LoadedClientTile, &LoadedClientTile, u8) -> bool;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LinkShape {
+    Single,
+    EndCap,
+    Straight,
+    Curve,
+    /// Mirror image of the curve, only used by directional tiles since rotating cannot mirror.
+    CurveMirrored,
+    TJunction,
+    Cross,
+}
+
+impl LinkShape {
+    /// Mask of the connected sides when the variant is drawn facing north.
+    /// Variant textures have to be drawn to match these.
+    fn canonical_mask(&self) -> u8 {
+        match self {
+            LinkShape::Single => 0,
+            LinkShape::EndCap => MASK_SOUTH,
+            LinkShape::Straight => MASK_NORTH | MASK_SOUTH,
+            LinkShape::Curve => MASK_SOUTH | MASK_EAST,
+            LinkShape::CurveMirrored => MASK_SOUTH | MASK_WEST,
+            LinkShape::TJunction => MASK_EAST | MASK_SOUTH | MASK_WEST,
+            LinkShape::Cross => MASK_NORTH | MASK_EAST | MASK_SOUTH | MASK_WEST,
+        }
+    }
+
+    fn from_mask(mask: u8) -> Self {
+        match mask.count_ones() {
+            0 => LinkShape::Single,
+            1 => LinkShape::EndCap,
+            2 if mask == MASK_NORTH | MASK_SOUTH || mask == MASK_EAST | MASK_WEST => {
+                LinkShape::Straight
+            }
+            2 => LinkShape::Curve,
+            3 => LinkShape::TJunction,
+            _ => LinkShape::Cross,
+        }
+    }
+
+    /// Shape of a directional tile from the sides feeding into it, relative to the tile facing north.
+    /// What lies in front of the tile does not change its look.
+    fn from_inputs(mask: u8) -> Self {
+        match mask & !MASK_NORTH {
+            0 => LinkShape::EndCap,
+            MASK_SOUTH => LinkShape::Straight,
+            MASK_EAST => LinkShape::Curve,
+            MASK_WEST => LinkShape::CurveMirrored,
+            inputs if inputs == MASK_EAST | MASK_WEST => LinkShape::TJunction,
+            _ => LinkShape::Cross,
+        }
+    }
+}
+
+/// The drawables a linkable tile picks from. Missing variants fall back to the straight piece.
+///
+/// Directional tiles, like belts, are always drawn in their own orientation. Their variants are drawn
+/// facing north and named after the sides feeding into them: the straight piece is fed from the south,
+/// the curve from the east, the mirrored curve from the west, the T-junction from both sides,
+/// the cross from the south and a side and the end cap from nowhere.
+#[derive(Clone)]
+pub struct ConnectedDrawables {
+    pub straight: Drawable,
+    pub end_cap: Option<Drawable>,
+    pub curve: Option<Drawable>,
+    pub curve_mirrored: Option<Drawable>,
+    pub t_junction: Option<Drawable>,
+    pub cross: Option<Drawable>,
+    pub directional: bool,
+}
+
+impl ConnectedDrawables {
+    pub fn new(straight: Drawable) -> Self {
+        Self {
+            straight,
+            end_cap: None,
+            curve: None,
+            curve_mirrored: None,
+            t_junction: None,
+            cross: None,
+            directional: false,
+        }
+    }
+
+    /// Picks variants by the sides feeding into the tile instead of rotating them to match the neighbours.
+    pub fn directional(mut self) -> Self {
+        self.directional = true;
+        self
+    }
+
+    pub fn with_end_cap(mut self, end_cap: Drawable) -> Self {
+        self.end_cap = Some(end_cap);
+        self
+    }
+
+    pub fn with_curve(mut self, curve: Drawable) -> Self {
+        self.curve = Some(curve);
+        self
+    }
+
+    pub fn with_curve_mirrored(mut self, curve_mirrored: Drawable) -> Self {
+        self.curve_mirrored = Some(curve_mirrored);
+        self
+    }
+
+    pub fn with_t_junction(mut self, t_junction: Drawable) -> Self {
+        self.t_junction = Some(t_junction);
+        self
+    }
+
+    pub fn with_cross(mut self, cross: Drawable) -> Self {
+        self.cross = Some(cross);
+        self
+    }
+
+    /// Falls back to the straight piece, which is then drawn in the tile's own orientation.
+    fn pick(&self, shape: LinkShape) -> (LinkShape, &Drawable) {
+        let variant = match shape {
+            LinkShape::EndCap => self.end_cap.as_ref(),
+            LinkShape::Curve => self.curve.as_ref(),
+            LinkShape::CurveMirrored => self.curve_mirrored.as_ref(),
+            LinkShape::TJunction => self.t_junction.as_ref(),
+            LinkShape::Cross => self.cross.as_ref(),
+            LinkShape::Single | LinkShape::Straight => None,
+        };
+        match variant {
+            Some(d) => (shape, d),
+            None => (LinkShape::Straight, &self.straight),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TileLinking {
+    pub rule: LinkRule,
+    pub variants: ConnectedDrawables,
+}
+
+/// The variant a linkable tile resolved to from its current neighbours.
+#[derive(Clone)]
+pub struct TileLink {
+    pub mask: u8,
+    pub shape: LinkShape,
+    pub drawable: Drawable,
+    pub orientation: Orientation,
+}
+
+const ROTATIONS: [Orientation; 4] = [
+    Orientation::North,
+    Orientation::East,
+    Orientation::South,
+    Orientation::West,
+];
+
+fn rotate_cw(mask: u8) -> u8 {
+    ((mask << 1) | (mask >> 3)) & 0xF
+}
+
+/// The side a tile facing this orientation points to.
+pub fn orientation_side(orientation: Orientation) -> u8 {
+    match orientation {
+        Orientation::North => MASK_NORTH,
+        Orientation::East => MASK_EAST,
+        Orientation::South => MASK_SOUTH,
+        Orientation::West => MASK_WEST,
+    }
+}
+
+pub fn opposite_side(side: u8) -> u8 {
+    rotate_cw(rotate_cw(side))
+}
+
+fn steps(orientation: Orientation) -> usize {
+    match orientation {
+        Orientation::North => 0,
+        Orientation::East => 1,
+        Orientation::South => 2,
+        Orientation::West => 3,
+    }
+}
+
+impl TileLinking {
+    pub fn connect(&self, mask: u8, own: Orientation) -> TileLink {
+        if self.variants.directional {
+            //turn the mask so the tile faces north, the variant is then drawn in the tile's own orientation
+            let mut local = mask;
+            for _ in 0..(4 - steps(own)) % 4 {
+                local = rotate_cw(local);
+            }
+            let (shape, drawable) = self.variants.pick(LinkShape::from_inputs(local));
+            return TileLink {
+                mask,
+                shape,
+                drawable: drawable.clone(),
+                orientation: own,
+            };
+        }
+        let (shape, drawable) = self.variants.pick(LinkShape::from_mask(mask));
+        let canonical = shape.canonical_mask();
+        //several rotations can match symmetric shapes, try the tile's own orientation first
+        let start = steps(own);
+        let orientation = (0..4)
+            .map(|i| (start + i) % 4)
+            .find(|k| {
+                let mut rotated = canonical;
+                for _ in 0..*k {
+                    rotated = rotate_cw(rotated);
+                }
+                rotated == mask
+            })
+            .map_or(own, |k| ROTATIONS[k]);
+        TileLink {
+            mask,
+            shape,
+            drawable: drawable.clone(),
+            orientation,
+        }
+    }
+}
+
+/// Default rule, tiles link to neighbours of the same kind.
+pub fn same_kind(tile: &LoadedClientTile, neighbour: &LoadedClientTile, _side: u8) -> bool {
+    tile.id == neighbour.id
+}
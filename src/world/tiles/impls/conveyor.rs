@@ -1,39 +1,241 @@
+use crate::drawutils;
+use crate::ingredients::{LoadedClientIngredient, CLIENT_INGREDIENT_REG, IG_SIZE};
 use crate::res::R;
 use crate::world::tiles::impls::ClientStateTile;
+use crate::world::tiles::connect::{opposite_side, orientation_side};
+use crate::world::tiles::LoadedClientTile;
+use api::ingredients::IngredientStack;
+use api::world::tiles::pos::TilePos;
+use api::world::tiles::Orientation;
 use bytebuffer::ByteBuffer;
 use mvengine::graphics::Drawable;
+use mvengine::ui::geometry::SimpleRect;
+use mvengine::ui::rendering::WideRenderContext;
 use mvutils::save::Savable;
 use mvutils::{lazy, Savable};
-use api::ingredients::IngredientStack;
+use std::any::Any;
+use std::time::Instant;
+
+pub const SLOTS: usize = 3;
+
+/// Assumed time between two server updates until we measured one.
+const DEFAULT_INTERVAL: f32 = 0.25;
 
 lazy! {
     pub static BASE: Drawable = Drawable::TileSet(R.tileset.conveyor, R.tile.conveyor.base);
+    pub static ANIMATED: Drawable = Drawable::Animation(R.animation.conveyor);
+    pub static CURVE: Drawable = Drawable::Animation(R.animation.conveyor_curve);
+    pub static CURVE_MIRRORED: Drawable = Drawable::Animation(R.animation.conveyor_curve_mirrored);
+    pub static T_JUNCTION: Drawable = Drawable::Animation(R.animation.conveyor_t_junction);
+    pub static END_CAP: Drawable = Drawable::Animation(R.animation.conveyor_end);
 }
 
+/// The part of the conveyor that is sent by the server.
 #[derive(Savable, Clone)]
+struct ConveyorState {
+    ingredients: [Option<IngredientStack>; SLOTS],
+}
+
+#[derive(Clone)]
 pub struct ClientConveyorTile {
-    ingredients: [Option<IngredientStack>; 3],
+    state: ConveyorState,
+    updated_at: Instant,
+    /// Estimated seconds between two server updates.
+    interval: f32,
+    /// Slot each ingredient started from at the last update, -1 means it came from the previous belt.
+    from: [f32; SLOTS],
 }
 
 impl ClientConveyorTile {
     pub fn new() -> Self {
         Self {
-            ingredients: [None, None, None],
+            state: ConveyorState {
+                ingredients: [None, None, None],
+            },
+            updated_at: Instant::now(),
+            interval: DEFAULT_INTERVAL,
+            from: [0.0, 1.0, 2.0],
         }
     }
+
+    /// Position of each slot along the belt from 0 (back) to 1 (front), interpolated since the last update.
+    fn slot_progress(&self, slot: usize) -> f32 {
+        let t = (self.updated_at.elapsed().as_secs_f32() / self.interval).clamp(0.0, 1.0);
+        let from = self.from[slot];
+        let pos = from + (slot as f32 - from) * t;
+        (pos + 0.5) / SLOTS as f32
+    }
+}
+
+fn same_stack(a: &Option<IngredientStack>, b: &Option<IngredientStack>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.ingredient == b.ingredient && a.amount == b.amount,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Slot every ingredient moved from between two server updates, -1 meaning the previous belt.
+/// Only slots whose content actually changed count as moved, so a stalled belt stays still.
+fn shift_origins(
+    old: &[Option<IngredientStack>; SLOTS],
+    new: &[Option<IngredientStack>; SLOTS],
+) -> [f32; SLOTS] {
+    std::array::from_fn(|i| {
+        let moved = new[i].is_some()
+            && !same_stack(&new[i], &old[i])
+            && (i == 0 || same_stack(&new[i], &old[i - 1]));
+        if moved {
+            i as f32 - 1.0
+        } else {
+            i as f32
+        }
+    })
 }
 
 impl ClientStateTile for ClientConveyorTile {
     fn load_from_server(&mut self, loader: &mut ByteBuffer) -> Result<(), String> {
-        *self = Self::load(loader)?;
+        self.state = ConveyorState::load(loader)?;
+        self.updated_at = Instant::now();
         Ok(())
     }
 
     fn get_drawable(&self) -> Drawable {
-        Drawable::Animation(R.animation.conveyor)
+        ANIMATED.clone()
     }
 
     fn box_clone(&self) -> Box<dyn ClientStateTile> {
         Box::new(self.clone())
     }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn carry_over(&mut self, previous: &dyn ClientStateTile) {
+        let Some(previous) = previous.as_any().downcast_ref::<ClientConveyorTile>() else {
+            return;
+        };
+        let elapsed = previous.updated_at.elapsed().as_secs_f32().clamp(0.05, 1.0);
+        self.interval = previous.interval * 0.7 + elapsed * 0.3;
+
+        self.from = shift_origins(&previous.state.ingredients, &self.state.ingredients);
+    }
+
+    fn describe(&self) -> Vec<String> {
+        self.state
+            .ingredients
+            .iter()
+            .enumerate()
+            .map(|(slot, stack)| match stack {
+                Some(stack) => {
+                    let name = CLIENT_INGREDIENT_REG
+                        .reference_object(stack.ingredient)
+                        .map_or(format!("ingredient {}", stack.ingredient), |i| i.label());
+                    format!("Slot {slot}: {name} x{}", stack.amount)
+                }
+                None => format!("Slot {slot}: empty"),
+            })
+            .collect()
+    }
+}
+
+/// Belts link to the belt they feed into and to every belt feeding into them.
+pub fn links(tile: &LoadedClientTile, neighbour: &LoadedClientTile, side: u8) -> bool {
+    if tile.id != neighbour.id {
+        return false;
+    }
+    side == orientation_side(tile.orientation)
+        || orientation_side(neighbour.orientation) == opposite_side(side)
+}
+
+/// Draws the belt and every ingredient on it at its position along the belt.
+pub fn draw(
+    ctx: &mut dyn WideRenderContext,
+    view_area: &SimpleRect,
+    pos: &TilePos,
+    tile_size: i32,
+    tile: &LoadedClientTile,
+    y: i32,
+) {
+    let rect = SimpleRect::new(
+        pos.raw.0 * tile_size,
+        pos.raw.1 * tile_size,
+        tile_size,
+        tile_size,
+    );
+    let (drawable, orientation) = tile.display();
+    drawutils::drawable_rect(ctx, view_area, &rect, drawable, orientation, y);
+
+    let Some(state) = &tile.state else {
+        return;
+    };
+    let Some(conveyor) = state.as_any().downcast_ref::<ClientConveyorTile>() else {
+        return;
+    };
+    let ts = tile_size as f64;
+    let half = (IG_SIZE.0 as f64 / ts / 2.0, IG_SIZE.1 as f64 / ts / 2.0);
+    for (slot, stack) in conveyor.state.ingredients.iter().enumerate() {
+        let Some(stack) = stack else {
+            continue;
+        };
+        let Some(ingredient) = LoadedClientIngredient::from_stack(stack) else {
+            continue;
+        };
+        let f = conveyor.slot_progress(slot) as f64;
+        let (lx, ly) = match tile.orientation {
+            Orientation::North => (0.5, f),
+            Orientation::South => (0.5, 1.0 - f),
+            Orientation::East => (f, 0.5),
+            Orientation::West => (1.0 - f, 0.5),
+        };
+        let at = (
+            pos.raw.0 as f64 + lx - half.0,
+            pos.raw.1 as f64 + ly - half.1,
+        );
+        ingredient.draw(ctx, view_area, at, tile_size, (y - 1) as f32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(ingredient: usize, amount: u32) -> Option<IngredientStack> {
+        Some(IngredientStack { ingredient, amount })
+    }
+
+    #[test]
+    fn stalled_belt_does_not_move() {
+        let belt = [stack(1, 1), stack(2, 1), stack(3, 1)];
+        assert_eq!(shift_origins(&belt, &belt), [0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn items_moving_forward_start_one_slot_back() {
+        let old = [stack(1, 1), stack(2, 1), None];
+        let new = [None, stack(1, 1), stack(2, 1)];
+        assert_eq!(shift_origins(&old, &new), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn items_entering_the_belt_come_from_the_previous_one() {
+        let old = [None, None, None];
+        let new = [stack(1, 1), None, None];
+        assert_eq!(shift_origins(&old, &new), [-1.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn changed_amounts_count_as_different_stacks() {
+        let old = [stack(1, 1), None, None];
+        let new = [stack(1, 2), stack(1, 1), None];
+        assert_eq!(shift_origins(&old, &new), [-1.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn appearing_items_without_a_source_do_not_slide() {
+        let old = [None, None, None];
+        let new = [None, stack(4, 1), None];
+        assert_eq!(shift_origins(&old, &new), [0.0, 1.0, 2.0]);
+    }
 }
@@ -5,6 +5,7 @@ use mvengine::graphics::Drawable;
 use mvutils::save::Savable;
 use mvutils::utils::TetrahedronOp;
 use mvutils::{lazy, Savable};
+use std::any::Any;
 use std::ops::Deref;
 
 lazy! {
@@ -37,4 +38,12 @@ impl ClientStateTile for ClientLampTile {
     fn box_clone(&self) -> Box<dyn ClientStateTile> {
         Box::new(self.clone())
     }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn describe(&self) -> Vec<String> {
+        vec![format!("Lamp: {}", self.on.yn("on", "off"))]
+    }
 }
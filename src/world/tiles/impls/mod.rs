@@ -1,13 +1,17 @@
 use crate::world::tiles::impls::conveyor::ClientConveyorTile;
 use crate::world::tiles::impls::lamp::ClientLampTile;
+use crate::world::tiles::connect::{ConnectedDrawables, LinkRule, TileLinking};
 use crate::world::tiles::LoadedClientTile;
 use api::registry::{Registerable, Registry};
 use api::world::tiles::pos::TilePos;
+use api::world::tiles::{Orientation, TileKind};
+use api::world::TileExtent;
 use bytebuffer::ByteBuffer;
 use mvengine::graphics::Drawable;
 use mvengine::ui::geometry::SimpleRect;
 use mvengine::ui::rendering::WideRenderContext;
 use mvutils::lazy;
+use std::any::Any;
 
 pub mod conveyor;
 pub mod lamp;
@@ -17,59 +21,157 @@ pub trait ClientStateTile {
     fn load_from_server(&mut self, loader: &mut ByteBuffer) -> Result<(), String>;
     fn get_drawable(&self) -> Drawable;
     fn box_clone(&self) -> Box<dyn ClientStateTile>;
+    fn as_any(&self) -> &dyn Any;
+
+    /// Called when the server replaced this tile's state, with the state it had before.
+    fn carry_over(&mut self, _previous: &dyn ClientStateTile) {}
+
+    /// Human readable lines about the current state, shown by the tile inspector.
+    fn describe(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// Palette tab a tile is listed under.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileCategory {
+    Building,
+    Logistics,
+    Lighting,
+    Misc,
+}
+
+impl TileCategory {
+    pub const ALL: [TileCategory; 4] = [
+        TileCategory::Building,
+        TileCategory::Logistics,
+        TileCategory::Lighting,
+        TileCategory::Misc,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TileCategory::Building => "Building",
+            TileCategory::Logistics => "Logistics",
+            TileCategory::Lighting => "Lighting",
+            TileCategory::Misc => "Misc",
+        }
+    }
 }
 
-pub type CustomDraw = fn(&mut dyn WideRenderContext, &SimpleRect, &TilePos, i32, &LoadedClientTile);
+pub type CustomDraw =
+    fn(&mut dyn WideRenderContext, &SimpleRect, &TilePos, i32, &LoadedClientTile, i32);
 
 pub struct ClientTile {
     pub id: usize,
+    /// Registry name, empty if the tile was registered without one.
+    pub name: &'static str,
+    /// Name shown to players, empty to fall back to the registry name.
+    pub display_name: &'static str,
+    pub category: TileCategory,
     pub base: Drawable,
     pub state: Option<Box<dyn ClientStateTile>>,
     pub drawer: Option<CustomDraw>,
+    pub linking: Option<TileLinking>,
+    /// Solid tiles block the player.
+    pub solid: bool,
+    /// Tiles covered once placed facing north, the server places anything bigger than one tile as a multitile.
+    pub footprint: TileExtent,
 }
 
 unsafe impl Send for ClientTile {}
 unsafe impl Sync for ClientTile {}
 
+impl ClientTile {
+    /// Name for the ui, falls back to the registry name and then the id.
+    pub fn label(&self) -> String {
+        if !self.display_name.is_empty() {
+            self.display_name.to_string()
+        } else if !self.name.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("Tile #{}", self.id + 1)
+        }
+    }
+
+    /// Tiles covered once placed facing `orientation`, east and west turn the footprint sideways.
+    pub fn extent(&self, orientation: Orientation) -> TileExtent {
+        match orientation {
+            Orientation::East | Orientation::West => (self.footprint.1, self.footprint.0),
+            _ => self.footprint,
+        }
+    }
+}
+
 impl Clone for ClientTile {
     fn clone(&self) -> Self {
         Self {
             id: self.id,
+            name: self.name,
+            display_name: self.display_name,
+            category: self.category,
             base: self.base.clone(),
             state: self.state.as_ref().map(|x| x.box_clone()),
             drawer: self.drawer,
+            linking: self.linking.clone(),
+            solid: self.solid,
+            footprint: self.footprint,
         }
     }
 }
 
 pub struct ClientTileCreateInfo {
+    name: &'static str,
+    display_name: &'static str,
+    category: TileCategory,
     base: Drawable,
     state: Option<Box<dyn ClientStateTile>>,
     drawer: Option<CustomDraw>,
+    linking: Option<TileLinking>,
+    solid: bool,
+    footprint: TileExtent,
 }
 
 impl ClientTileCreateInfo {
     pub fn no_state(base: Drawable) -> Self {
         Self {
+            name: "",
+            display_name: "",
+            category: TileCategory::Misc,
             base,
             state: None,
             drawer: None,
+            linking: None,
+            solid: true,
+            footprint: (1, 1),
         }
     }
 
     pub fn stateful<S: ClientStateTile + 'static>(base: Drawable, state: S) -> Self {
         Self {
+            name: "",
+            display_name: "",
+            category: TileCategory::Misc,
             base,
             state: Some(Box::new(state)),
             drawer: None,
+            linking: None,
+            solid: true,
+            footprint: (1, 1),
         }
     }
 
     pub fn no_state_custom_draw(base: Drawable, drawer: CustomDraw) -> Self {
         Self {
+            name: "",
+            display_name: "",
+            category: TileCategory::Misc,
             base,
             state: None,
             drawer: Some(drawer),
+            linking: None,
+            solid: true,
+            footprint: (1, 1),
         }
     }
 
@@ -79,22 +181,66 @@ impl ClientTileCreateInfo {
         drawer: CustomDraw,
     ) -> Self {
         Self {
+            name: "",
+            display_name: "",
+            category: TileCategory::Misc,
             base,
             state: Some(Box::new(state)),
             drawer: Some(drawer),
+            linking: None,
+            solid: true,
+            footprint: (1, 1),
         }
     }
 }
 
+impl ClientTileCreateInfo {
+    pub fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Name and palette tab players see for this tile.
+    pub fn display(mut self, display_name: &'static str, category: TileCategory) -> Self {
+        self.display_name = display_name;
+        self.category = category;
+        self
+    }
+
+    /// Makes the tile pick its drawable from the neighbours it links to.
+    pub fn linkable(mut self, rule: LinkRule, variants: ConnectedDrawables) -> Self {
+        self.linking = Some(TileLinking { rule, variants });
+        self
+    }
+
+    /// Lets players walk over the tile, tiles are solid by default.
+    pub fn walkable(mut self) -> Self {
+        self.solid = false;
+        self
+    }
+
+    /// Width and height the tile covers facing north, one tile by default.
+    pub fn footprint(mut self, width: i32, height: i32) -> Self {
+        self.footprint = (width, height);
+        self
+    }
+}
+
 impl Registerable for ClientTile {
     type CreateInfo = ClientTileCreateInfo;
 
     fn with_id(id: usize, info: Self::CreateInfo) -> Self {
         Self {
             id,
+            name: info.name,
+            display_name: info.display_name,
+            category: info.category,
             base: info.base,
             state: info.state,
             drawer: info.drawer,
+            linking: info.linking,
+            solid: info.solid,
+            footprint: info.footprint,
         }
     }
 }
@@ -103,14 +249,42 @@ lazy! {
     pub static CLIENT_TILE_REG: Registry<ClientTile> = Registry::new();
 }
 
+/// Server tile ids start at 1, 0 means no tile.
+pub fn tile_template(id: TileKind) -> Option<&'static ClientTile> {
+    if id < 1 {
+        return None;
+    }
+    CLIENT_TILE_REG.reference_object(id as usize - 1)
+}
+
 pub fn register_tiles() {
-    CLIENT_TILE_REG.register(ClientTileCreateInfo::no_state(wood::BASE.clone()));
-    CLIENT_TILE_REG.register(ClientTileCreateInfo::stateful(
-        lamp::BASE.clone(),
-        ClientLampTile::new(),
-    ));
-    CLIENT_TILE_REG.register(ClientTileCreateInfo::stateful(
-        conveyor::BASE.clone(),
-        ClientConveyorTile::new(),
-    ));
+    CLIENT_TILE_REG.register(
+        ClientTileCreateInfo::no_state(wood::BASE.clone())
+            .named("wood")
+            .display("Wood", TileCategory::Building),
+    );
+    CLIENT_TILE_REG.register(
+        ClientTileCreateInfo::stateful(lamp::BASE.clone(), ClientLampTile::new())
+            .named("lamp")
+            .display("Lamp", TileCategory::Lighting),
+    );
+    CLIENT_TILE_REG.register(
+        ClientTileCreateInfo::stateful_custom_draw(
+            conveyor::BASE.clone(),
+            ClientConveyorTile::new(),
+            conveyor::draw,
+        )
+        .named("conveyor")
+        .display("Conveyor", TileCategory::Logistics)
+        .linkable(
+            conveyor::links,
+            ConnectedDrawables::new(conveyor::ANIMATED.clone())
+                .with_curve(conveyor::CURVE.clone())
+                .with_curve_mirrored(conveyor::CURVE_MIRRORED.clone())
+                .with_t_junction(conveyor::T_JUNCTION.clone())
+                .with_end_cap(conveyor::END_CAP.clone())
+                .directional(),
+        )
+        .walkable(),
+    );
 }
@@ -1,11 +1,13 @@
+pub mod connect;
 pub mod impls;
 
 use crate::drawutils;
 use crate::drawutils::Fill;
 use crate::game::Game;
 use crate::res::R;
-use crate::world::terrain_tex_mapper::get_terrain_drawable;
-use crate::world::tiles::impls::{ClientStateTile, CustomDraw, CLIENT_TILE_REG};
+use crate::world::terrain::get_terrain_drawable;
+use crate::world::tiles::connect::TileLink;
+use crate::world::tiles::impls::{tile_template, ClientStateTile, CustomDraw, CLIENT_TILE_REG};
 use api::world::chunk::ToClientObject;
 use api::world::tiles::pos::TilePos;
 use api::world::tiles::{Orientation, TileKind};
@@ -45,6 +47,8 @@ pub struct LoadedClientTile {
     pub orientation: Orientation,
     pub drawer: Option<CustomDraw>,
     pub state: Option<Box<dyn ClientStateTile>>,
+    /// Set for linkable tiles once their neighbours are known.
+    pub link: Option<TileLink>,
 }
 
 unsafe impl Send for LoadedClientTile {}
@@ -58,8 +62,39 @@ impl LoadedClientTile {
             orientation: Orientation::North,
             drawer: None,
             state: None,
+            link: None,
         }
     }
+
+    /// The drawable and orientation this tile is drawn with, taking its links into account.
+    pub fn display(&self) -> (&Drawable, Orientation) {
+        if let Some(link) = &self.link {
+            (&link.drawable, link.orientation)
+        } else {
+            (&self.texture, self.orientation)
+        }
+    }
+
+    /// Whether replacing `other` with this tile leaves the cached chunk geometry valid.
+    /// Custom drawers read the state every frame, so only the way they are placed matters.
+    pub fn looks_like(&self, other: &LoadedClientTile) -> bool {
+        if self.id != other.id
+            || self.orientation != other.orientation
+            || self.drawer.is_some() != other.drawer.is_some()
+        {
+            return false;
+        }
+        self.drawer.is_some() || same_drawable(&self.texture, &other.texture)
+    }
+}
+
+fn same_drawable(a: &Drawable, b: &Drawable) -> bool {
+    match (a, b) {
+        (Drawable::Texture(a), Drawable::Texture(b)) => a == b,
+        (Drawable::Animation(a), Drawable::Animation(b)) => a == b,
+        (Drawable::TileSet(a, i), Drawable::TileSet(b, j)) => a == b && i == j,
+        _ => false,
+    }
 }
 
 impl LoadedClientTile {
@@ -71,7 +106,7 @@ impl LoadedClientTile {
         let state = server_tile.state;
 
         let (drawable, drawer, state) = if is_terrain {
-            (get_terrain_drawable(server_tile.id as usize), None, None)
+            (get_terrain_drawable(server_tile.id), None, None)
         } else {
             if server_tile.id < 1 {
                 return None;
@@ -111,17 +146,19 @@ impl LoadedClientTile {
             orientation,
             drawer,
             state,
+            link: None,
         })
     }
 
     pub fn new_ghost(id: TileKind, orientation: Orientation) -> Self {
-        if let Some(template) = CLIENT_TILE_REG.create_object(id as usize) {
+        if let Some(template) = tile_template(id) {
             Self {
                 id,
-                texture: template.base,
+                texture: template.base.clone(),
                 orientation,
                 drawer: template.drawer,
-                state: template.state,
+                state: template.state.as_ref().map(|s| s.box_clone()),
+                link: None,
             }
         } else {
             Self::void()
@@ -160,12 +197,12 @@ pub enum ClientDrawable {
 }
 
 impl ClientDrawable {
-    pub fn get_texture(&self) -> (&Texture, Vec4) {
-        match self {
+    pub fn get_texture(&self) -> (&'static Texture, Vec4) {
+        match *self {
             ClientDrawable::Texture(t) => (t, Vec4::default_uv()),
             ClientDrawable::Animation(a) => a.get_current(),
             ClientDrawable::TileSet(t, i) => {
-                if let Some(r) = t.get_tile(*i) {
+                if let Some(r) = t.get_tile(i) {
                     r
                 } else {
                     let tex = R.resolve_texture(R.mv.texture.missing).unwrap();